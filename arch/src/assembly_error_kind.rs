@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AssemblyErrorKind {
     InvalidInstruction { name: String },
     InvalidRegister { id: usize },
+    InvalidRegisterRange { id: usize, size: usize },
     InvalidPin { id: usize },
     TooLarge,
 }
@@ -1,10 +1,72 @@
-use crate::{Location, SourceUnit};
-use serde::{Deserialize, Serialize};
+use crate::{Argument, Location, SourceUnit};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DebugInfo {
     #[serde(alias = "sourceLoc")]
     pub source_loc: BTreeMap<usize, Location>,
-    pub unit: SourceUnit,
+    /// Files of the program, the main one goes first. Debug info with the
+    /// single `unit` it had before includes is read as one file.
+    #[serde(alias = "unit", deserialize_with = "one_or_more_units")]
+    pub units: Vec<SourceUnit>,
+    /// Label names and the addresses they point to.
+    #[serde(default)]
+    pub labels: BTreeMap<String, usize>,
+    /// Constants defined with `.equ` and their values.
+    #[serde(default)]
+    pub constants: BTreeMap<String, Argument>,
+    /// Locations of macro invocations, indexed by [`Location::expansion`].
+    #[serde(default)]
+    pub expansions: Vec<Location>,
+}
+
+impl DebugInfo {
+    /// The file that `location` points into.
+    pub fn unit(&self, location: Location) -> Option<&SourceUnit> {
+        self.units.get(location.unit)
+    }
+
+    /// Finds the nearest label at or before `address`.
+    pub fn label_for(&self, address: usize) -> Option<(&str, usize)> {
+        self.labels
+            .iter()
+            .filter(|(_, label)| **label <= address)
+            .max_by_key(|(_, label)| **label)
+            .map(|(name, label)| (name.as_str(), *label))
+    }
+}
+
+fn one_or_more_units<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<SourceUnit>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Units {
+        One(SourceUnit),
+        Many(Vec<SourceUnit>),
+    }
+
+    Ok(match Units::deserialize(deserializer)? {
+        Units::One(unit) => vec![unit],
+        Units::Many(units) => units,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DebugInfo;
+    use crate::SourceUnit;
+
+    #[test]
+    fn single_unit() {
+        let unit = SourceUnit::new_anonymous(b"halt".to_vec());
+        let json = format!(
+            r#"{{"source_loc": {{}}, "unit": {}}}"#,
+            serde_json::to_string(&unit).unwrap()
+        );
+        let info: DebugInfo = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(info.units, [unit]);
+    }
 }
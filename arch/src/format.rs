@@ -0,0 +1,200 @@
+//! Format strings used by `dbgf`.
+//!
+//! Placeholders:
+//! - `{}` - a value as is,
+//! - `{i}` - an integer,
+//! - `{.N}` - a float with `N` digits after the point, up to
+//!   [`MAX_PRECISION`],
+//! - `{x}` - a hexadecimal integer.
+//!
+//! `{{` and `}}` are printed as `{` and `}`.
+
+/// Most digits after the point a float placeholder can print.
+pub const MAX_PRECISION: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatSpec {
+    Default,
+    Int,
+    Float { precision: usize },
+    Hex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatSegment {
+    Text { text: String },
+    Placeholder { spec: FormatSpec },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError {
+    pub message: String,
+    pub offset: usize,
+}
+
+impl FormatError {
+    fn new(message: String, offset: usize) -> Self {
+        Self { message, offset }
+    }
+}
+
+pub fn parse(format: &str) -> Result<Vec<FormatSegment>, FormatError> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut chars = format.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '{' if matches!(chars.peek(), Some((_, '{'))) => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if matches!(chars.peek(), Some((_, '}'))) => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut spec = String::new();
+                let mut closed = false;
+
+                for (_, c) in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+
+                    spec.push(c);
+                }
+
+                if !closed {
+                    return Err(FormatError::new(
+                        String::from("Unclosed placeholder"),
+                        offset,
+                    ));
+                }
+
+                if !text.is_empty() {
+                    segments.push(FormatSegment::Text {
+                        text: std::mem::take(&mut text),
+                    });
+                }
+
+                segments.push(FormatSegment::Placeholder {
+                    spec: parse_spec(&spec, offset)?,
+                });
+            }
+            '}' => {
+                return Err(FormatError::new(
+                    String::from("Unmatched `}`, use `}}` to print it"),
+                    offset,
+                ))
+            }
+            _ => text.push(c),
+        }
+    }
+
+    if !text.is_empty() {
+        segments.push(FormatSegment::Text { text });
+    }
+
+    Ok(segments)
+}
+
+pub fn placeholders_count(segments: &[FormatSegment]) -> usize {
+    segments
+        .iter()
+        .filter(|segment| matches!(segment, FormatSegment::Placeholder { .. }))
+        .count()
+}
+
+pub fn format_value(spec: FormatSpec, value: f32) -> String {
+    match spec {
+        FormatSpec::Default => value.to_string(),
+        FormatSpec::Int => (value as i32).to_string(),
+        FormatSpec::Float { precision } => format!("{value:.precision$}"),
+        FormatSpec::Hex => format!("{:#x}", value as i32),
+    }
+}
+
+fn parse_spec(spec: &str, offset: usize) -> Result<FormatSpec, FormatError> {
+    match spec {
+        "" => Ok(FormatSpec::Default),
+        "i" => Ok(FormatSpec::Int),
+        "x" => Ok(FormatSpec::Hex),
+        _ => {
+            if let Some(Ok(precision)) = spec.strip_prefix('.').map(str::parse::<usize>) {
+                if precision > MAX_PRECISION {
+                    return Err(FormatError::new(
+                        format!("Precision {precision} is above the maximum of {MAX_PRECISION}"),
+                        offset,
+                    ));
+                }
+
+                Ok(FormatSpec::Float { precision })
+            } else {
+                Err(FormatError::new(
+                    format!("Unknown placeholder: {{{spec}}}"),
+                    offset,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FormatSegment, FormatSpec};
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            super::parse("x = {}, y = {.2} {{{i}}} {x}"),
+            Ok(vec![
+                FormatSegment::Text {
+                    text: String::from("x = ")
+                },
+                FormatSegment::Placeholder {
+                    spec: FormatSpec::Default
+                },
+                FormatSegment::Text {
+                    text: String::from(", y = ")
+                },
+                FormatSegment::Placeholder {
+                    spec: FormatSpec::Float { precision: 2 }
+                },
+                FormatSegment::Text {
+                    text: String::from(" {")
+                },
+                FormatSegment::Placeholder {
+                    spec: FormatSpec::Int
+                },
+                FormatSegment::Text {
+                    text: String::from("} ")
+                },
+                FormatSegment::Placeholder {
+                    spec: FormatSpec::Hex
+                },
+            ])
+        )
+    }
+
+    #[test]
+    fn parse_fail() {
+        assert!(super::parse("{").is_err());
+        assert!(super::parse("}").is_err());
+        assert!(super::parse("{f}").is_err());
+        assert!(super::parse("{.2000000000}").is_err());
+        assert!(super::parse("{.16}").is_ok());
+    }
+
+    #[test]
+    fn format_value() {
+        assert_eq!(super::format_value(FormatSpec::Default, 1.5), "1.5");
+        assert_eq!(super::format_value(FormatSpec::Int, 1.5), "1");
+        assert_eq!(
+            super::format_value(FormatSpec::Float { precision: 3 }, 1.5),
+            "1.500"
+        );
+        assert_eq!(super::format_value(FormatSpec::Hex, 255.0), "0xff");
+    }
+}
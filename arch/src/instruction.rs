@@ -1,7 +1,707 @@
-use crate::{Argument, Operation};
+use crate::{
+    Argument, AssemblyErrorKind, InstructionError, Limits, Operation, RegisterKind, RegisterMode,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Instruction {
     pub operation: Operation,
     pub arguments: Vec<Argument>,
 }
+
+impl Instruction {
+    fn validate_arguments(&self, limits: &Limits) -> Result<(), InstructionError> {
+        for (index, arg) in self.arguments.iter().enumerate() {
+            match arg {
+                Argument::Register {
+                    register: RegisterKind::Regular { id, .. },
+                } if *id >= limits.regular_registers => {
+                    return Err(InstructionError::new(
+                        format!(
+                            "Register's id {id} is out of bounds (maximum: {})",
+                            limits.regular_registers - 1
+                        ),
+                        Some(index),
+                        AssemblyErrorKind::InvalidRegister { id: *id },
+                    ));
+                }
+                Argument::Pin { id } if *id >= limits.pins => {
+                    return Err(InstructionError::new(
+                        format!(
+                            "Pin's id {id} is out of bounds (maximum: {})",
+                            limits.pins - 1
+                        ),
+                        Some(index),
+                        AssemblyErrorKind::InvalidPin { id: *id },
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `arg`, which the operation jumps to, is an address or a
+    /// register holding one.
+    fn validate_address(
+        op: &Operation,
+        index: usize,
+        arg: &Argument,
+    ) -> Result<(), InstructionError> {
+        if matches!(arg, Argument::Int { .. } | Argument::Register { .. }) {
+            return Ok(());
+        }
+
+        Err(InstructionError::new(
+            format!("{op}'s target accepts only numbers and registers"),
+            Some(index),
+            AssemblyErrorKind::InvalidInstruction {
+                name: op.to_string(),
+            },
+        ))
+    }
+
+    /// Checks that `arg` is a direct register and the `size` registers
+    /// starting from it exist.
+    fn validate_vector(
+        op: &Operation,
+        index: usize,
+        arg: &Argument,
+        size: usize,
+        limits: &Limits,
+    ) -> Result<(), InstructionError> {
+        let Argument::Register {
+            register:
+                RegisterKind::Regular {
+                    id,
+                    mode: RegisterMode::Direct,
+                },
+        } = arg
+        else {
+            return Err(InstructionError::new(
+                format!("{op}'s vector arguments accept only direct registers"),
+                Some(index),
+                AssemblyErrorKind::InvalidInstruction {
+                    name: op.to_string(),
+                },
+            ));
+        };
+
+        if id + size > limits.regular_registers {
+            return Err(InstructionError::new(
+                format!(
+                    "Registers R{id}..R{} are out of bounds (maximum: {})",
+                    id + size - 1,
+                    limits.regular_registers - 1
+                ),
+                Some(index),
+                AssemblyErrorKind::InvalidRegisterRange { id: *id, size },
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the number and kinds of arguments the operation accepts and
+    /// that registers and pins fit in `limits`.
+    pub fn validate(&self, limits: &Limits) -> Result<(), InstructionError> {
+        self.validate_arguments(limits)?;
+
+        let Instruction {
+            arguments: args,
+            operation: op,
+        } = self;
+
+        match op {
+            Operation::Add | Operation::Sub | Operation::Mul | Operation::Div | Operation::Mod => {
+                if args.len() != 3 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 3 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. } | RegisterKind::ProgramCounter
+                    }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Jmp => {
+                if args.len() != 1 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 1 argument"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                Self::validate_address(op, 0, &args[0])?;
+            }
+            Operation::Dbg => {
+                if args.len() != 1 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 1 argument"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Dbgs => {
+                if args.len() != 1 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 1 argument"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Dbgf => {
+                if args.is_empty() {
+                    return Err(InstructionError::new(
+                        format!("{op} requires at least 1 argument"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Yield | Operation::Halt => {
+                if !args.is_empty() {
+                    return Err(InstructionError::new(
+                        format!("{op} does not accept arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Mov => {
+                if args.len() != 2 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 2 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. } | RegisterKind::ProgramCounter
+                    } | Argument::Pin { .. }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers and pins"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Beq
+            | Operation::Bge
+            | Operation::Bgt
+            | Operation::Ble
+            | Operation::Blt
+            | Operation::Bne => {
+                if args.len() != 3 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 3 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                Self::validate_address(op, 2, &args[2])?;
+            }
+            Operation::Beqz
+            | Operation::Bgez
+            | Operation::Bgtz
+            | Operation::Blez
+            | Operation::Bltz
+            | Operation::Bnez => {
+                if args.len() != 2 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 2 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                Self::validate_address(op, 1, &args[1])?;
+            }
+            Operation::Seq
+            | Operation::Sge
+            | Operation::Sgt
+            | Operation::Sle
+            | Operation::Slt
+            | Operation::Sne => {
+                if args.len() != 3 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 3 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. } | RegisterKind::ProgramCounter
+                    }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Seqz
+            | Operation::Sgez
+            | Operation::Sgtz
+            | Operation::Slez
+            | Operation::Sltz
+            | Operation::Snez => {
+                if args.len() != 2 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 2 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. } | RegisterKind::ProgramCounter
+                    }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Push => {
+                if args.len() != 1 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 1 argument"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                };
+            }
+            Operation::Pop | Operation::Peek => {
+                if args.len() != 1 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 1 argument"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                };
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. } | RegisterKind::ProgramCounter
+                    }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Ret => {
+                if !args.is_empty() {
+                    return Err(InstructionError::new(
+                        format!("{op} does not accept arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Spawn => {
+                if args.len() != 2 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 2 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. }
+                    }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                Self::validate_address(op, 1, &args[1])?;
+            }
+            Operation::Switch
+            | Operation::Join
+            | Operation::Cancel
+            | Operation::Sleep
+            | Operation::Sleepms
+            | Operation::Wait => {
+                if args.len() != 1 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 1 argument"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Assert => {
+                if !(1..=2).contains(&args.len()) {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 1 or 2 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Asserteq | Operation::Assertne => {
+                if !(2..=3).contains(&args.len()) {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 2 or 3 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Call => {
+                if args.len() != 1 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 1 argument"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                Self::validate_address(op, 0, &args[0])?;
+            }
+            Operation::And
+            | Operation::Or
+            | Operation::Xor
+            | Operation::Nor
+            | Operation::Andi
+            | Operation::Ori
+            | Operation::Xori
+            | Operation::Shr
+            | Operation::Shl
+            | Operation::Ror
+            | Operation::Rol => {
+                if args.len() != 3 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 3 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. } | RegisterKind::ProgramCounter
+                    }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Sqrt
+            | Operation::Trunc
+            | Operation::Ceil
+            | Operation::Floor
+            | Operation::Abs
+            | Operation::Exp
+            | Operation::Inf
+            | Operation::Nan
+            | Operation::Sin
+            | Operation::Cos
+            | Operation::Tan
+            | Operation::Asin
+            | Operation::Acos
+            | Operation::Sign => {
+                if args.len() != 2 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 2 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. } | RegisterKind::ProgramCounter
+                    }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Max
+            | Operation::Min
+            | Operation::Log
+            | Operation::Rand
+            | Operation::Randi
+            | Operation::Atan2
+            | Operation::Pow => {
+                if args.len() != 3 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 3 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. } | RegisterKind::ProgramCounter
+                    }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Lerp | Operation::Clamp | Operation::Select => {
+                if args.len() != 4 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 4 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. } | RegisterKind::ProgramCounter
+                    }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Vadd { size } | Operation::Vsub { size } | Operation::Vscale { size } => {
+                if args.len() != 3 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 3 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                Self::validate_vector(op, 0, &args[0], *size, limits)?;
+                Self::validate_vector(op, 1, &args[1], *size, limits)?;
+
+                if !matches!(op, Operation::Vscale { .. }) {
+                    Self::validate_vector(op, 2, &args[2], *size, limits)?;
+                }
+            }
+            Operation::Vcross => {
+                if args.len() != 3 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 3 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                for (index, arg) in args.iter().enumerate() {
+                    Self::validate_vector(op, index, arg, 3, limits)?;
+                }
+            }
+            Operation::Vnorm { size } => {
+                if args.len() != 2 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 2 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                Self::validate_vector(op, 0, &args[0], *size, limits)?;
+                Self::validate_vector(op, 1, &args[1], *size, limits)?;
+            }
+            Operation::Vdot { size } | Operation::Vlen { size } => {
+                let count = if matches!(op, Operation::Vdot { .. }) {
+                    3
+                } else {
+                    2
+                };
+
+                if args.len() != count {
+                    return Err(InstructionError::new(
+                        format!("{op} requires {count} arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. } | RegisterKind::ProgramCounter
+                    }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                for (index, arg) in args.iter().enumerate().skip(1) {
+                    Self::validate_vector(op, index, arg, *size, limits)?;
+                }
+            }
+            Operation::Lb | Operation::Lh | Operation::Lw => {
+                if args.len() != 2 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 2 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+
+                if !matches!(
+                    args[0],
+                    Argument::Register {
+                        register: RegisterKind::Regular { .. } | RegisterKind::ProgramCounter
+                    }
+                ) {
+                    return Err(InstructionError::new(
+                        format!("{op}'s first argument accepts only registers"),
+                        Some(0),
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+            Operation::Sb | Operation::Sh | Operation::Sw => {
+                if args.len() != 2 {
+                    return Err(InstructionError::new(
+                        format!("{op} requires 2 arguments"),
+                        None,
+                        AssemblyErrorKind::InvalidInstruction {
+                            name: op.to_string(),
+                        },
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use crate::AssemblyErrorKind;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstructionError {
+    message: String,
+    argument: Option<usize>,
+    kind: AssemblyErrorKind,
+}
+
+impl InstructionError {
+    pub fn new(message: String, argument: Option<usize>, kind: AssemblyErrorKind) -> Self {
+        Self {
+            message,
+            argument,
+            kind,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Index of the offending argument, `None` when the instruction as a
+    /// whole is wrong, e.g. it has too many arguments.
+    pub fn argument(&self) -> Option<usize> {
+        self.argument
+    }
+
+    pub fn kind(&self) -> &AssemblyErrorKind {
+        &self.kind
+    }
+}
@@ -3,7 +3,9 @@ mod assembly;
 mod assembly_error;
 mod assembly_error_kind;
 mod debug_info;
+pub mod format;
 mod instruction;
+mod instruction_error;
 mod limits;
 mod location;
 pub mod math;
@@ -18,6 +20,7 @@ pub use assembly_error::AssemblyError;
 pub use assembly_error_kind::AssemblyErrorKind;
 pub use debug_info::DebugInfo;
 pub use instruction::Instruction;
+pub use instruction_error::InstructionError;
 pub use limits::Limits;
 pub use location::Location;
 pub use opeartion::Operation;
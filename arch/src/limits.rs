@@ -9,6 +9,13 @@ pub struct Limits {
     pub stack_size: usize,
     #[serde(alias = "ramLength")]
     pub ram_length: usize,
+    /// Maximum number of coroutines alive at once, including the main one.
+    #[serde(default = "default_coroutines")]
+    pub coroutines: usize,
+}
+
+fn default_coroutines() -> usize {
+    16
 }
 
 impl Default for Limits {
@@ -18,6 +25,22 @@ impl Default for Limits {
             pins: 8,
             stack_size: 256,
             ram_length: 16384,
+            coroutines: default_coroutines(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Limits;
+
+    #[test]
+    fn without_coroutines() {
+        let limits: Limits = serde_json::from_str(
+            r#"{"regularRegisters": 16, "pins": 8, "stackSize": 256, "ramLength": 16384}"#,
+        )
+        .unwrap();
+
+        assert_eq!(limits, Limits::default());
+    }
+}
@@ -5,6 +5,14 @@ pub struct Location {
     pub line: usize,
     pub column: usize,
     pub offset: usize,
+    /// Index of the file in [`crate::DebugInfo::units`], the main file is
+    /// `0`.
+    #[serde(default)]
+    pub unit: usize,
+    /// Index of the macro invocation that produced this location, if it
+    /// comes from a macro body, see [`crate::DebugInfo::expansions`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expansion: Option<usize>,
 }
 
 impl Default for Location {
@@ -13,6 +21,8 @@ impl Default for Location {
             line: 1,
             column: 1,
             offset: 0,
+            unit: 0,
+            expansion: None,
         }
     }
 }
@@ -23,6 +33,24 @@ impl Location {
             line,
             column: row,
             offset,
+            ..Self::default()
         }
     }
+
+    /// Locations of the macro invocations this location was expanded from,
+    /// innermost first. `expansions` are the invocations indexed by
+    /// [`Location::expansion`].
+    pub fn invocations(self, expansions: &[Location]) -> impl Iterator<Item = Location> + '_ {
+        std::iter::successors(
+            self.expansion.and_then(|id| expansions.get(id)),
+            |invocation| invocation.expansion.and_then(|id| expansions.get(id)),
+        )
+        .copied()
+    }
+
+    /// The location in the code as it was written, i.e. the outermost
+    /// macro invocation.
+    pub fn root(self, expansions: &[Location]) -> Location {
+        self.invocations(expansions).last().unwrap_or(self)
+    }
 }
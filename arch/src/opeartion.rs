@@ -11,6 +11,7 @@ pub enum Operation {
     Jmp,
     Dbg,
     Dbgs,
+    Dbgf,
     Yield,
     Beq,
     Beqz,
@@ -70,6 +71,36 @@ pub enum Operation {
     Sb,
     Sh,
     Sw,
+    Rand,
+    Randi,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan2,
+    Pow,
+    Lerp,
+    Clamp,
+    Sign,
+    Select,
+    Vadd { size: usize },
+    Vsub { size: usize },
+    Vscale { size: usize },
+    Vdot { size: usize },
+    Vlen { size: usize },
+    Vnorm { size: usize },
+    Vcross,
+    Spawn,
+    Switch,
+    Join,
+    Cancel,
+    Sleep,
+    Sleepms,
+    Wait,
+    Assert,
+    Asserteq,
+    Assertne,
 }
 
 impl Display for Operation {
@@ -83,6 +114,7 @@ impl Display for Operation {
             Operation::Mod => f.write_str("mod"),
             Operation::Dbg => f.write_str("dbg"),
             Operation::Dbgs => f.write_str("dbgs"),
+            Operation::Dbgf => f.write_str("dbgf"),
             Operation::Yield => f.write_str("yield"),
             Operation::Beq => f.write_str("beq"),
             Operation::Beqz => f.write_str("beqz"),
@@ -143,6 +175,36 @@ impl Display for Operation {
             Operation::Sb => f.write_str("sb"),
             Operation::Sh => f.write_str("sh"),
             Operation::Sw => f.write_str("sw"),
+            Operation::Rand => f.write_str("rand"),
+            Operation::Randi => f.write_str("randi"),
+            Operation::Sin => f.write_str("sin"),
+            Operation::Cos => f.write_str("cos"),
+            Operation::Tan => f.write_str("tan"),
+            Operation::Asin => f.write_str("asin"),
+            Operation::Acos => f.write_str("acos"),
+            Operation::Atan2 => f.write_str("atan2"),
+            Operation::Pow => f.write_str("pow"),
+            Operation::Lerp => f.write_str("lerp"),
+            Operation::Clamp => f.write_str("clamp"),
+            Operation::Sign => f.write_str("sign"),
+            Operation::Select => f.write_str("select"),
+            Operation::Vadd { size } => f.write_fmt(format_args!("vadd{size}")),
+            Operation::Vsub { size } => f.write_fmt(format_args!("vsub{size}")),
+            Operation::Vscale { size } => f.write_fmt(format_args!("vscale{size}")),
+            Operation::Vdot { size } => f.write_fmt(format_args!("vdot{size}")),
+            Operation::Vlen { size } => f.write_fmt(format_args!("vlen{size}")),
+            Operation::Vnorm { size } => f.write_fmt(format_args!("vnorm{size}")),
+            Operation::Vcross => f.write_str("vcross"),
+            Operation::Spawn => f.write_str("spawn"),
+            Operation::Switch => f.write_str("switch"),
+            Operation::Join => f.write_str("join"),
+            Operation::Cancel => f.write_str("cancel"),
+            Operation::Sleep => f.write_str("sleep"),
+            Operation::Sleepms => f.write_str("sleepms"),
+            Operation::Wait => f.write_str("wait"),
+            Operation::Assert => f.write_str("assert"),
+            Operation::Asserteq => f.write_str("asserteq"),
+            Operation::Assertne => f.write_str("assertne"),
         }
     }
 }
@@ -165,6 +227,7 @@ impl FromStr for Operation {
             "mod" => Ok(Self::Mod),
             "dbg" => Ok(Self::Dbg),
             "dbgs" => Ok(Self::Dbgs),
+            "dbgf" => Ok(Self::Dbgf),
             "yield" => Ok(Self::Yield),
             "beq" => Ok(Self::Beq),
             "beqz" => Ok(Self::Beqz),
@@ -225,6 +288,105 @@ impl FromStr for Operation {
             "sb" => Ok(Operation::Sb),
             "sh" => Ok(Operation::Sh),
             "sw" => Ok(Operation::Sw),
+            "rand" => Ok(Operation::Rand),
+            "randi" => Ok(Operation::Randi),
+            "sin" => Ok(Operation::Sin),
+            "cos" => Ok(Operation::Cos),
+            "tan" => Ok(Operation::Tan),
+            "asin" => Ok(Operation::Asin),
+            "acos" => Ok(Operation::Acos),
+            "atan2" => Ok(Operation::Atan2),
+            "pow" => Ok(Operation::Pow),
+            "lerp" => Ok(Operation::Lerp),
+            "clamp" => Ok(Operation::Clamp),
+            "sign" => Ok(Operation::Sign),
+            "select" => Ok(Operation::Select),
+            "vcross" => Ok(Operation::Vcross),
+            "spawn" => Ok(Operation::Spawn),
+            "switch" => Ok(Operation::Switch),
+            "join" => Ok(Operation::Join),
+            "cancel" => Ok(Operation::Cancel),
+            "sleep" => Ok(Operation::Sleep),
+            "sleepms" => Ok(Operation::Sleepms),
+            "wait" => Ok(Operation::Wait),
+            "assert" => Ok(Operation::Assert),
+            "asserteq" => Ok(Operation::Asserteq),
+            "assertne" => Ok(Operation::Assertne),
+            _ => Self::parse_vector(s),
+        }
+    }
+}
+
+impl Operation {
+    /// Branches that jump only when their condition holds.
+    pub fn is_conditional_branch(&self) -> bool {
+        matches!(
+            self,
+            Operation::Beq
+                | Operation::Beqz
+                | Operation::Bge
+                | Operation::Bgez
+                | Operation::Bgt
+                | Operation::Bgtz
+                | Operation::Ble
+                | Operation::Blez
+                | Operation::Blt
+                | Operation::Bltz
+                | Operation::Bne
+                | Operation::Bnez
+        )
+    }
+
+    /// Operations whose first argument is the register they write to.
+    pub fn writes_first_argument(&self) -> bool {
+        !matches!(
+            self,
+            Operation::Jmp
+                | Operation::Dbg
+                | Operation::Dbgs
+                | Operation::Dbgf
+                | Operation::Yield
+                | Operation::Halt
+                | Operation::Push
+                | Operation::Ret
+                | Operation::Call
+                | Operation::Sb
+                | Operation::Sh
+                | Operation::Sw
+                | Operation::Switch
+                | Operation::Join
+                | Operation::Cancel
+                | Operation::Sleep
+                | Operation::Sleepms
+                | Operation::Wait
+                | Operation::Assert
+                | Operation::Asserteq
+                | Operation::Assertne
+        ) && !self.is_conditional_branch()
+    }
+
+    /// Vector operations work with 2, 3 or 4 consecutive registers.
+    pub const VECTOR_SIZES: std::ops::RangeInclusive<usize> = 2..=4;
+
+    fn parse_vector(s: &str) -> Result<Self, OpeartionParseError> {
+        let Some((name, size)) = s.split_at_checked(s.len().saturating_sub(1)) else {
+            return Err(OpeartionParseError::UnknownOperation);
+        };
+        let Ok(size) = size.parse::<usize>() else {
+            return Err(OpeartionParseError::UnknownOperation);
+        };
+
+        if !Self::VECTOR_SIZES.contains(&size) {
+            return Err(OpeartionParseError::UnknownOperation);
+        }
+
+        match name {
+            "vadd" => Ok(Operation::Vadd { size }),
+            "vsub" => Ok(Operation::Vsub { size }),
+            "vscale" => Ok(Operation::Vscale { size }),
+            "vdot" => Ok(Operation::Vdot { size }),
+            "vlen" => Ok(Operation::Vlen { size }),
+            "vnorm" => Ok(Operation::Vnorm { size }),
             _ => Err(OpeartionParseError::UnknownOperation),
         }
     }
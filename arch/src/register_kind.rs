@@ -9,6 +9,7 @@ pub enum RegisterKind {
     Regular { id: usize, mode: RegisterMode },
     ProgramCounter,
     StackPointer,
+    Clock,
 }
 
 impl Display for RegisterKind {
@@ -17,6 +18,7 @@ impl Display for RegisterKind {
             RegisterKind::Regular { id, .. } => f.write_fmt(format_args!("R{id}")),
             RegisterKind::ProgramCounter => f.write_str("PC"),
             RegisterKind::StackPointer => f.write_str("SP"),
+            RegisterKind::Clock => f.write_str("CLK"),
         }
     }
 }
@@ -70,6 +70,19 @@ impl SourceUnit {
         self.lines.len()
     }
 
+    /// Returns the text of the line `number`, counted from 1, without its
+    /// line ending.
+    pub fn line(&self, number: usize) -> Option<&[u8]> {
+        let line = self.lines.get(number.checked_sub(1)?)?;
+        let text = &self.data[line.start..line.end];
+
+        Some(
+            text.strip_suffix(b"\r\n")
+                .or_else(|| text.strip_suffix(b"\n"))
+                .unwrap_or(text),
+        )
+    }
+
     pub fn find_location(&self, position: usize) -> Option<Location> {
         for (idx, line) in self.lines.iter().enumerate() {
             if position >= line.start && position < line.end {
@@ -77,6 +90,7 @@ impl SourceUnit {
                     line: idx + 1,
                     column: ((position - line.start) % line.end) + 1,
                     offset: position,
+                    ..Location::default()
                 });
             }
         }
@@ -106,6 +120,18 @@ mod tests {
         assert_eq!(unit.lines_count(), 5)
     }
 
+    #[test]
+    fn line() {
+        let source = "line1\r\nline2\nline3";
+        let unit = SourceUnit::new(String::from("<anonymous>"), source.as_bytes().to_vec());
+
+        assert_eq!(unit.line(1), Some(&b"line1"[..]));
+        assert_eq!(unit.line(2), Some(&b"line2"[..]));
+        assert_eq!(unit.line(3), Some(&b"line3"[..]));
+        assert_eq!(unit.line(0), None);
+        assert_eq!(unit.line(4), None);
+    }
+
     #[test]
     fn empty() {
         let source = "";
@@ -136,7 +162,8 @@ dbg $pc
             Some(Location {
                 line: 2,
                 column: 1,
-                offset: 1
+                offset: 1,
+                ..Location::default()
             })
         );
         assert_eq!(
@@ -144,7 +171,8 @@ dbg $pc
             Some(Location {
                 line: 2,
                 column: 9,
-                offset: 9
+                offset: 9,
+                ..Location::default()
             })
         );
         assert_eq!(
@@ -152,7 +180,8 @@ dbg $pc
             Some(Location {
                 line: 3,
                 column: 1,
-                offset: 19
+                offset: 19,
+                ..Location::default()
             })
         );
 
@@ -164,7 +193,8 @@ dbg $pc
             Some(Location {
                 line: 1,
                 column: 1,
-                offset: 0
+                offset: 0,
+                ..Location::default()
             })
         )
     }
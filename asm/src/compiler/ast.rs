@@ -2,74 +2,187 @@ use std::collections::BTreeMap;
 
 use crate::parser::{Token, TokenKind};
 
-use super::{syntax_token::SyntaxToken, CompilationError, CompilationErrorKind};
+use super::{symbols, syntax_token::SyntaxToken, CompilationError, CompilationErrorKind};
 
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct Ast {
     pub labels: BTreeMap<String, usize>,
+    /// Constants defined with `.equ`, each one has its value as the only
+    /// child.
+    pub constants: BTreeMap<String, SyntaxToken>,
     pub tokens: Vec<SyntaxToken>,
+    /// Data directives in the order of their appearance.
+    pub data: Vec<SyntaxToken>,
+    /// Labels placed right before a data directive, pointing to its index
+    /// in [`Ast::data`].
+    pub data_labels: BTreeMap<String, usize>,
 }
 
 impl Ast {
-    pub fn new(tokens: &[Token]) -> Result<Self, CompilationError> {
+    /// Builds the tree of `tokens`, every invalid statement is reported and
+    /// skipped with its arguments.
+    pub fn new(tokens: &[Token]) -> Result<Self, Vec<CompilationError>> {
         let mut ast = Self::default();
-        let mut current_operation: Option<SyntaxToken> = None;
+        let mut current: Option<SyntaxToken> = None;
+        // Labels not followed by an operation yet, they may label data.
+        let mut pending: Vec<String> = Vec::new();
+        let mut errors = Vec::new();
+        let mut skipping = false;
 
         for token in tokens {
-            match &token.kind {
-                TokenKind::Comment { .. } | TokenKind::Label { .. } => {
-                    if let Some(operation) = current_operation.as_ref() {
-                        ast.tokens.push(operation.clone())
-                    }
+            if skipping && matches!(token.kind, TokenKind::Argument { .. }) {
+                continue;
+            }
 
-                    current_operation = None;
+            skipping = false;
 
-                    if let TokenKind::Label { name } = &token.kind {
-                        if ast.labels.contains_key(name) {
-                            return Err(CompilationError::new(
-                                format!("Duplicate label: {name}"),
-                                token.location,
-                                CompilationErrorKind::DuplicateLabel { name: name.clone() },
-                            ));
-                        }
+            // Anything but an argument ends the previous statement, which is
+            // reported on its own so the token is still added.
+            if !matches!(token.kind, TokenKind::Argument { .. }) {
+                if let Err(error) = ast.finish(current.take()) {
+                    errors.push(error);
+                }
+            }
 
-                        ast.labels.insert(name.clone(), ast.tokens.len());
-                    }
+            if let Err(error) = ast.add(token, &mut current, &mut pending) {
+                errors.push(error);
+                skipping = true;
+            }
+        }
 
-                    continue;
-                }
-                TokenKind::Operation { .. } => {
-                    if let Some(operation) = current_operation.as_ref() {
-                        ast.tokens.push(operation.clone())
-                    }
+        if let Err(error) = ast.finish(current) {
+            errors.push(error);
+        }
 
-                    current_operation = Some(SyntaxToken {
-                        token: token.clone(),
-                        child: vec![],
-                    });
-                }
-                TokenKind::Argument { .. } => {
-                    let Some(operation) = current_operation.as_mut() else {
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        Ok(ast)
+    }
+
+    fn add(
+        &mut self,
+        token: &Token,
+        current: &mut Option<SyntaxToken>,
+        pending: &mut Vec<String>,
+    ) -> Result<(), CompilationError> {
+        match &token.kind {
+            TokenKind::Comment { .. } | TokenKind::DocComment { .. } | TokenKind::Label { .. } => {
+                if let TokenKind::Label { name } = &token.kind {
+                    if self.labels.contains_key(name) || self.data_labels.contains_key(name) {
                         return Err(CompilationError::new(
-                            String::from("Expected an operation but got an argument"),
+                            format!("Duplicate label: {name}"),
                             token.location,
-                            CompilationErrorKind::InvalidArgument,
+                            CompilationErrorKind::DuplicateLabel { name: name.clone() },
                         ));
-                    };
+                    }
 
-                    operation.child.push(SyntaxToken {
-                        token: token.clone(),
-                        child: Vec::new(),
-                    });
+                    self.labels.insert(name.clone(), self.tokens.len());
+                    pending.push(name.clone());
                 }
             }
+            TokenKind::Operation { .. } => {
+                pending.clear();
+
+                *current = Some(SyntaxToken {
+                    token: token.clone(),
+                    child: vec![],
+                });
+            }
+            TokenKind::Constant { name } => {
+                if symbols::BUILTIN_CONSTANTS.contains(&name.as_str()) {
+                    return Err(CompilationError::new(
+                        format!("Constant {name} shadows a built-in constant"),
+                        token.location,
+                        CompilationErrorKind::InvalidConstant { name: name.clone() },
+                    ));
+                }
+
+                if let Some(previous) = self.constants.get(name) {
+                    return Err(CompilationError::new(
+                        format!(
+                            "Duplicate constant: {name}, first defined at line {}",
+                            previous.token.location.line
+                        ),
+                        token.location,
+                        CompilationErrorKind::DuplicateConstant { name: name.clone() },
+                    ));
+                }
+
+                *current = Some(SyntaxToken {
+                    token: token.clone(),
+                    child: vec![],
+                });
+            }
+            TokenKind::Data { .. } => {
+                for name in pending.drain(..) {
+                    self.labels.remove(&name);
+                    self.data_labels.insert(name, self.data.len());
+                }
+
+                *current = Some(SyntaxToken {
+                    token: token.clone(),
+                    child: vec![],
+                });
+            }
+            TokenKind::Macro { .. } | TokenKind::EndMacro => {
+                return Err(CompilationError::new(
+                    String::from("Macros should be expanded before building an AST"),
+                    token.location,
+                    CompilationErrorKind::InvalidArgument,
+                ));
+            }
+            TokenKind::Include { path } => {
+                return Err(CompilationError::new(
+                    format!("{path} should be included with parser::parse_with_includes"),
+                    token.location,
+                    CompilationErrorKind::UnresolvedInclude { path: path.clone() },
+                ));
+            }
+            TokenKind::Argument { .. } => {
+                let Some(current_syntax) = current.as_mut() else {
+                    return Err(CompilationError::new(
+                        String::from("Expected an operation but got an argument"),
+                        token.location,
+                        CompilationErrorKind::InvalidArgument,
+                    ));
+                };
+
+                current_syntax.child.push(SyntaxToken {
+                    token: token.clone(),
+                    child: Vec::new(),
+                });
+            }
         }
 
-        if let Some(operation) = current_operation.as_ref() {
-            ast.tokens.push(operation.clone())
+        Ok(())
+    }
+
+    /// Stores an operation, a constant definition or a data directive with
+    /// its arguments.
+    fn finish(&mut self, syntax: Option<SyntaxToken>) -> Result<(), CompilationError> {
+        let Some(syntax) = syntax else {
+            return Ok(());
+        };
+
+        match &syntax.token.kind {
+            TokenKind::Constant { name } => {
+                if syntax.child.len() != 1 {
+                    return Err(CompilationError::new(
+                        format!("Constant {name} requires 1 value"),
+                        syntax.token.location,
+                        CompilationErrorKind::InvalidConstant { name: name.clone() },
+                    ));
+                }
+
+                self.constants.insert(name.clone(), syntax);
+            }
+            TokenKind::Data { .. } => self.data.push(syntax),
+            _ => self.tokens.push(syntax),
         }
 
-        Ok(ast)
+        Ok(())
     }
 }
 
@@ -104,12 +217,16 @@ add $r0 1 0
             ast,
             Ok(Ast {
                 labels,
+                constants: BTreeMap::new(),
+                data: Vec::new(),
+                data_labels: BTreeMap::new(),
                 tokens: vec![SyntaxToken {
                     token: Token {
                         location: Location {
                             line: 3,
                             column: 1,
-                            offset: 8
+                            offset: 8,
+                            ..Location::default()
                         },
                         kind: TokenKind::Operation {
                             operation: String::from("add")
@@ -121,7 +238,8 @@ add $r0 1 0
                                 location: Location {
                                     line: 3,
                                     column: 5,
-                                    offset: 12
+                                    offset: 12,
+                                    ..Location::default()
                                 },
                                 kind: TokenKind::Argument {
                                     argument: ArgumentToken::Register {
@@ -139,7 +257,8 @@ add $r0 1 0
                                 location: Location {
                                     line: 3,
                                     column: 9,
-                                    offset: 16
+                                    offset: 16,
+                                    ..Location::default()
                                 },
                                 kind: TokenKind::Argument {
                                     argument: ArgumentToken::Int { value: 1 }
@@ -152,7 +271,8 @@ add $r0 1 0
                                 location: Location {
                                     line: 3,
                                     column: 11,
-                                    offset: 18
+                                    offset: 18,
+                                    ..Location::default()
                                 },
                                 kind: TokenKind::Argument {
                                     argument: ArgumentToken::Int { value: 0 }
@@ -8,6 +8,8 @@ pub struct CompilationError {
     message: String,
     location: Location,
     kind: CompilationErrorKind,
+    #[serde(default, skip_serializing_if = "<[Location]>::is_empty")]
+    expanded_from: Box<[Location]>,
 }
 
 impl CompilationError {
@@ -16,6 +18,7 @@ impl CompilationError {
             message,
             location,
             kind,
+            expanded_from: Box::default(),
         }
     }
 
@@ -30,4 +33,17 @@ impl CompilationError {
     pub fn kind(&self) -> &CompilationErrorKind {
         &self.kind
     }
+
+    /// Macro invocations the error's location was expanded from, innermost
+    /// first.
+    pub fn expanded_from(&self) -> &[Location] {
+        &self.expanded_from
+    }
+
+    /// Resolves the macro invocations of the error's location in
+    /// `expansions`.
+    pub(crate) fn in_expansions(mut self, expansions: &[Location]) -> Self {
+        self.expanded_from = self.location.invocations(expansions).collect();
+        self
+    }
 }
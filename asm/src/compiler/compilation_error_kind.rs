@@ -1,3 +1,4 @@
+use nano_risc_arch::AssemblyErrorKind;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -8,4 +9,19 @@ pub enum CompilationErrorKind {
     UnknownLabel { name: String },
     TooLargeAssembly { size: usize },
     UnknownConstant { name: String },
+    InvalidFormat,
+    FormatArgumentsMismatch { expected: usize, found: usize },
+    InvalidMacro { name: String },
+    DuplicateMacro { name: String },
+    UnterminatedMacro { name: String },
+    UnexpectedEndMacro,
+    RecursiveMacro { name: String },
+    MacroArgumentsMismatch { expected: usize, found: usize },
+    UnresolvedInclude { path: String },
+    InvalidConstant { name: String },
+    DuplicateConstant { name: String },
+    InvalidExpression,
+    ExpressionOverflow,
+    InvalidData,
+    InvalidInstruction { kind: AssemblyErrorKind },
 }
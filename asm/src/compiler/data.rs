@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+
+use nano_risc_arch::Argument;
+
+use super::{symbols::Symbols, Ast, CompilationError, CompilationErrorKind, SyntaxToken};
+use crate::parser::{ArgumentToken, DataKind, TokenKind};
+
+/// Places the data directives at the start of the text section and points
+/// their labels to them.
+///
+/// Sizes are computed first, so values may refer to any label, while the
+/// size of `.space` may only refer to the data before it.
+pub(crate) fn emit_data(ast: &Ast, symbols: &mut Symbols, errors: &mut Vec<CompilationError>) {
+    let mut labels: BTreeMap<usize, Vec<&String>> = BTreeMap::new();
+    let mut offsets = Vec::with_capacity(ast.data.len());
+    let mut size = 0;
+
+    for (name, index) in &ast.data_labels {
+        labels.entry(*index).or_default().push(name);
+    }
+
+    for (index, syntax) in ast.data.iter().enumerate() {
+        for name in labels.get(&index).into_iter().flatten() {
+            let address = symbols.text_address(size);
+
+            symbols.data_labels.insert(name.to_string(), address);
+        }
+
+        match data_size(syntax, symbols) {
+            Ok(data_size) => {
+                offsets.push(Some(size));
+                size += data_size;
+            }
+            Err(error) => {
+                offsets.push(None);
+                errors.push(error);
+            }
+        }
+    }
+
+    symbols.text_section.resize(size, 0);
+
+    for (syntax, offset) in ast.data.iter().zip(offsets) {
+        let Some(offset) = offset else {
+            continue;
+        };
+
+        match data_bytes(syntax, symbols) {
+            Ok(bytes) => symbols.text_section[offset..offset + bytes.len()].copy_from_slice(&bytes),
+            Err(error) => errors.push(error),
+        }
+    }
+}
+
+fn data_kind(syntax: &SyntaxToken) -> DataKind {
+    let TokenKind::Data { kind } = &syntax.token.kind else {
+        unreachable!("Ast only stores data directives as data")
+    };
+
+    *kind
+}
+
+fn data_size(syntax: &SyntaxToken, symbols: &mut Symbols) -> Result<usize, CompilationError> {
+    let count = syntax.child.len();
+    let size = match data_kind(syntax) {
+        DataKind::Byte => count,
+        DataKind::Word | DataKind::Float => count * 4,
+        DataKind::Space => {
+            let [value] = single(syntax, "a size")?;
+
+            if matches!(argument(value), ArgumentToken::String { .. }) {
+                return Err(invalid_data(value, String::from(".space expects a size")));
+            }
+
+            match symbols.argument(argument(value), value.token.location)? {
+                Argument::Int { value: size } if size >= 0 => size as usize,
+                _ => {
+                    return Err(invalid_data(
+                        value,
+                        String::from(".space expects a non-negative integer size"),
+                    ))
+                }
+            }
+        }
+        DataKind::String => {
+            let [value] = single(syntax, "a string")?;
+            let ArgumentToken::String { value: text } = argument(value) else {
+                return Err(invalid_data(
+                    value,
+                    String::from(".string expects a string"),
+                ));
+            };
+
+            text.len() + 1
+        }
+    };
+
+    if count == 0 {
+        return Err(CompilationError::new(
+            String::from("Data directive requires at least 1 value"),
+            syntax.token.location,
+            CompilationErrorKind::InvalidData,
+        ));
+    }
+
+    Ok(size)
+}
+
+fn data_bytes(syntax: &SyntaxToken, symbols: &mut Symbols) -> Result<Vec<u8>, CompilationError> {
+    let kind = data_kind(syntax);
+    let mut bytes = Vec::new();
+
+    match kind {
+        DataKind::Space => bytes.resize(data_size(syntax, symbols)?, 0),
+        DataKind::String => {
+            if let ArgumentToken::String { value } = argument(&syntax.child[0]) {
+                bytes.extend_from_slice(value.as_bytes());
+                bytes.push(0);
+            }
+        }
+        DataKind::Byte | DataKind::Word | DataKind::Float => {
+            for value in &syntax.child {
+                let resolved = symbols.argument(argument(value), value.token.location)?;
+
+                match (kind, resolved) {
+                    (DataKind::Byte, Argument::Int { value: byte })
+                        if (-128..=255).contains(&byte) =>
+                    {
+                        bytes.push(byte as u8)
+                    }
+                    (DataKind::Byte, Argument::Int { value: byte }) => {
+                        return Err(invalid_data(
+                            value,
+                            format!("Byte value is out of range: {byte}"),
+                        ))
+                    }
+                    (DataKind::Word, Argument::Int { value }) => {
+                        bytes.extend_from_slice(&value.to_le_bytes())
+                    }
+                    (DataKind::Float, Argument::Int { value }) => {
+                        bytes.extend_from_slice(&(value as f32).to_le_bytes())
+                    }
+                    (DataKind::Float, Argument::Float { value }) => {
+                        bytes.extend_from_slice(&value.to_le_bytes())
+                    }
+                    _ => {
+                        return Err(invalid_data(
+                            value,
+                            format!("Invalid value for {kind:?} data"),
+                        ))
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Returns the only value of `syntax`.
+fn single<'a>(
+    syntax: &'a SyntaxToken,
+    expected: &str,
+) -> Result<&'a [SyntaxToken; 1], CompilationError> {
+    syntax.child.as_slice().try_into().map_err(|_| {
+        CompilationError::new(
+            format!("Data directive expects {expected}"),
+            syntax.token.location,
+            CompilationErrorKind::InvalidData,
+        )
+    })
+}
+
+fn argument(value: &SyntaxToken) -> &ArgumentToken {
+    let TokenKind::Argument { argument } = &value.token.kind else {
+        unreachable!("Data directives are followed by arguments")
+    };
+
+    argument
+}
+
+fn invalid_data(value: &SyntaxToken, message: String) -> CompilationError {
+    CompilationError::new(
+        message,
+        value.token.location,
+        CompilationErrorKind::InvalidData,
+    )
+}
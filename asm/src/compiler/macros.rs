@@ -0,0 +1,468 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
+
+use nano_risc_arch::{Location, Operation};
+
+use super::{pseudo, scopes, CompilationError, CompilationErrorKind};
+use crate::parser::{ArgumentToken, ExpressionToken, Token, TokenKind};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Macro {
+    location: Location,
+    parameters: Vec<String>,
+    body: Vec<Token>,
+}
+
+#[derive(Debug, Default)]
+struct Expander {
+    macros: BTreeMap<String, Macro>,
+    /// Invocations of every expansion, indexed by [`Location::expansion`].
+    expansions: Vec<Location>,
+    errors: Vec<CompilationError>,
+}
+
+/// Removes macro definitions from `tokens` and replaces every invocation
+/// with the macro's body.
+///
+/// ```text
+/// .macro save_call target reg
+///     push reg
+///     call target
+///     pop reg
+/// .endm
+///
+/// save_call print $r0
+/// ```
+///
+/// Parameters are referenced by their names and replaced with the
+/// invocation's arguments. Labels defined in a body are renamed to
+/// `name@N`, where `N` is unique for every expansion, so a macro can be used
+/// more than once. Tokens produced by an expansion keep their location in
+/// the body, its [`Location::expansion`] indexes the invocation locations
+/// returned with the tokens.
+///
+/// A broken definition or invocation is reported and skipped, so every
+/// error is returned at once.
+pub fn expand_macros(
+    tokens: Vec<Token>,
+) -> Result<(Vec<Token>, Vec<Location>), Vec<CompilationError>> {
+    let mut expander = Expander::default();
+    let tokens = expander.collect(tokens);
+    let tokens = expander.expand(tokens, &mut Vec::new());
+
+    if expander.errors.is_empty() {
+        Ok((tokens, expander.expansions))
+    } else {
+        Err(super::sorted_errors(expander.errors, &expander.expansions))
+    }
+}
+
+impl Expander {
+    /// Moves macro definitions out of `tokens`. A definition with an error
+    /// still takes its body up to `.endm`, but isn't added.
+    fn collect(&mut self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut output = Vec::new();
+        // The macro being defined and whether it's added at `.endm`.
+        let mut definition: Option<(String, Macro, bool)> = None;
+
+        for token in tokens {
+            match token.kind {
+                TokenKind::Macro { name, params } => {
+                    if let Some((outer, _, _)) = &definition {
+                        self.errors.push(CompilationError::new(
+                            format!("Macro {name} can't be defined inside macro {outer}"),
+                            token.location,
+                            CompilationErrorKind::InvalidMacro { name },
+                        ));
+                        continue;
+                    }
+
+                    let mut valid = true;
+
+                    if Operation::from_str(&name).is_ok() || pseudo::is_pseudo_instruction(&name) {
+                        self.errors.push(CompilationError::new(
+                            format!("Macro {name} shadows an operation"),
+                            token.location,
+                            CompilationErrorKind::InvalidMacro { name: name.clone() },
+                        ));
+                        valid = false;
+                    }
+
+                    if let Some(previous) = self.macros.get(&name) {
+                        self.errors.push(CompilationError::new(
+                            format!(
+                                "Duplicate macro: {name}, first defined at line {}",
+                                previous.location.line
+                            ),
+                            token.location,
+                            CompilationErrorKind::DuplicateMacro { name: name.clone() },
+                        ));
+                        valid = false;
+                    }
+
+                    let mut unique = BTreeSet::new();
+
+                    if let Some(parameter) = params.iter().find(|p| !unique.insert(*p)) {
+                        self.errors.push(CompilationError::new(
+                            format!("Macro {name} has duplicate parameter: {parameter}"),
+                            token.location,
+                            CompilationErrorKind::InvalidMacro { name: name.clone() },
+                        ));
+                        valid = false;
+                    }
+
+                    definition = Some((
+                        name,
+                        Macro {
+                            location: token.location,
+                            parameters: params,
+                            body: Vec::new(),
+                        },
+                        valid,
+                    ));
+                }
+                TokenKind::EndMacro => match definition.take() {
+                    Some((name, definition, true)) => {
+                        self.macros.insert(name, definition);
+                    }
+                    Some(_) => {}
+                    None => self.errors.push(CompilationError::new(
+                        String::from(".endm without a macro definition"),
+                        token.location,
+                        CompilationErrorKind::UnexpectedEndMacro,
+                    )),
+                },
+                kind => {
+                    let token = Token {
+                        location: token.location,
+                        kind,
+                    };
+
+                    match definition.as_mut() {
+                        Some((_, definition, _)) => definition.body.push(token),
+                        None => output.push(token),
+                    }
+                }
+            }
+        }
+
+        if let Some((name, definition, _)) = definition {
+            self.errors.push(CompilationError::new(
+                format!("Macro {name} is missing .endm"),
+                definition.location,
+                CompilationErrorKind::UnterminatedMacro { name },
+            ));
+        }
+
+        output
+    }
+
+    /// Replaces invocations in `tokens`, `stack` holds the macros being
+    /// expanded to detect recursion. An invalid invocation is dropped.
+    fn expand(&mut self, tokens: Vec<Token>, stack: &mut Vec<String>) -> Vec<Token> {
+        let mut output = Vec::new();
+        let mut tokens = tokens.into_iter().peekable();
+
+        while let Some(token) = tokens.next() {
+            let TokenKind::Operation { operation: name } = &token.kind else {
+                output.push(token);
+                continue;
+            };
+            let Some(definition) = self.macros.get(name).cloned() else {
+                output.push(token);
+                continue;
+            };
+            let mut arguments = Vec::new();
+
+            while let Some(argument) =
+                tokens.next_if(|token| matches!(token.kind, TokenKind::Argument { .. }))
+            {
+                arguments.push(argument);
+            }
+
+            if stack.contains(name) {
+                self.errors.push(CompilationError::new(
+                    format!("Macro {name} invokes itself"),
+                    token.location,
+                    CompilationErrorKind::RecursiveMacro { name: name.clone() },
+                ));
+                continue;
+            }
+
+            if arguments.len() != definition.parameters.len() {
+                self.errors.push(CompilationError::new(
+                    format!(
+                        "Macro {name} expects {} arguments but got {}",
+                        definition.parameters.len(),
+                        arguments.len()
+                    ),
+                    token.location,
+                    CompilationErrorKind::MacroArgumentsMismatch {
+                        expected: definition.parameters.len(),
+                        found: arguments.len(),
+                    },
+                ));
+                continue;
+            }
+
+            let expansion = self.expansions.len();
+
+            self.expansions.push(token.location);
+
+            let body = Self::instantiate(&definition, arguments, expansion);
+
+            stack.push(name.clone());
+            output.append(&mut self.expand(body, stack));
+            stack.pop();
+        }
+
+        output
+    }
+
+    /// Copies the body of `definition` with parameters substituted and local
+    /// labels renamed.
+    fn instantiate(definition: &Macro, arguments: Vec<Token>, expansion: usize) -> Vec<Token> {
+        let arguments: BTreeMap<&String, Token> =
+            definition.parameters.iter().zip(arguments).collect();
+        let labels: BTreeSet<&String> = definition
+            .body
+            .iter()
+            .filter_map(|token| match &token.kind {
+                TokenKind::Label { name } if !scopes::is_anonymous(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+        let local = |name: &String| format!("{name}@{}", expansion + 1);
+        let operand = |name: &String| {
+            let argument = match arguments.get(name).map(|token| &token.kind) {
+                Some(TokenKind::Argument { argument }) => ExpressionToken::from_argument(argument),
+                _ => None,
+            };
+
+            match argument {
+                Some(argument) => argument,
+                None if labels.contains(name) => ExpressionToken::Label { name: local(name) },
+                None => ExpressionToken::Label { name: name.clone() },
+            }
+        };
+
+        definition
+            .body
+            .iter()
+            .map(|token| {
+                let location = Location {
+                    expansion: Some(expansion),
+                    ..token.location
+                };
+
+                match &token.kind {
+                    TokenKind::Label { name } if labels.contains(name) => Token {
+                        location,
+                        kind: TokenKind::Label { name: local(name) },
+                    },
+                    TokenKind::Argument {
+                        argument: ArgumentToken::Label { name },
+                    } => {
+                        if let Some(argument) = arguments.get(name) {
+                            argument.clone()
+                        } else if labels.contains(name) {
+                            Token {
+                                location,
+                                kind: TokenKind::Argument {
+                                    argument: ArgumentToken::Label { name: local(name) },
+                                },
+                            }
+                        } else {
+                            Token {
+                                location,
+                                kind: token.kind.clone(),
+                            }
+                        }
+                    }
+                    TokenKind::Argument {
+                        argument: ArgumentToken::Expression { expression },
+                    } => Token {
+                        location,
+                        kind: TokenKind::Argument {
+                            argument: ArgumentToken::Expression {
+                                expression: expression.map_labels(&operand),
+                            },
+                        },
+                    },
+                    kind => Token {
+                        location,
+                        kind: kind.clone(),
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand_macros;
+    use crate::{
+        compiler::CompilationErrorKind,
+        parser::{self, ArgumentToken, Token, TokenKind},
+    };
+    use nano_risc_arch::{Location, RegisterKind, RegisterMode, SourceUnit};
+    use pretty_assertions::assert_eq;
+
+    fn expand(source: &str) -> Result<(Vec<Token>, Vec<Location>), Vec<super::CompilationError>> {
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+
+        expand_macros(parser::parse(&unit).unwrap())
+    }
+
+    #[test]
+    fn expansion() {
+        let source = r#"
+.macro bump reg
+again:
+    add reg reg 1
+    jmp again
+.endm
+bump $r2
+"#;
+        let invocation = Location::new(7, 1, 62);
+        let register = Token {
+            location: Location::new(7, 6, 67),
+            kind: TokenKind::Argument {
+                argument: ArgumentToken::Register {
+                    register: RegisterKind::Regular {
+                        id: 2,
+                        mode: RegisterMode::Direct,
+                    },
+                },
+            },
+        };
+
+        let (tokens, expansions) = expand(source).unwrap();
+
+        assert_eq!(expansions, [invocation]);
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    location: Location {
+                        expansion: Some(0),
+                        ..Location::new(3, 1, 17)
+                    },
+                    kind: TokenKind::Label {
+                        name: String::from("again@1")
+                    }
+                },
+                Token {
+                    location: Location {
+                        expansion: Some(0),
+                        ..Location::new(4, 5, 28)
+                    },
+                    kind: TokenKind::Operation {
+                        operation: String::from("add")
+                    }
+                },
+                register.clone(),
+                register,
+                Token {
+                    location: Location {
+                        expansion: Some(0),
+                        ..Location::new(4, 17, 40)
+                    },
+                    kind: TokenKind::Argument {
+                        argument: ArgumentToken::Int { value: 1 }
+                    }
+                },
+                Token {
+                    location: Location {
+                        expansion: Some(0),
+                        ..Location::new(5, 5, 46)
+                    },
+                    kind: TokenKind::Operation {
+                        operation: String::from("jmp")
+                    }
+                },
+                Token {
+                    location: Location {
+                        expansion: Some(0),
+                        ..Location::new(5, 9, 50)
+                    },
+                    kind: TokenKind::Argument {
+                        argument: ArgumentToken::Label {
+                            name: String::from("again@1")
+                        }
+                    }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let kind = |source: &str| {
+            expand(source).map_err(|errors| {
+                errors
+                    .iter()
+                    .map(|error| error.kind().clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(
+            kind(".macro a\n.endm\n.macro a\n.endm"),
+            Err(vec![CompilationErrorKind::DuplicateMacro {
+                name: String::from("a")
+            }])
+        );
+        assert_eq!(
+            kind(".macro a x\n.endm\na"),
+            Err(vec![CompilationErrorKind::MacroArgumentsMismatch {
+                expected: 1,
+                found: 0
+            }])
+        );
+        assert_eq!(
+            kind(".macro a\nc\n.endm\n.macro c\na\n.endm\na"),
+            Err(vec![CompilationErrorKind::RecursiveMacro {
+                name: String::from("a")
+            }])
+        );
+        assert_eq!(
+            kind(".macro a\nadd $r0 1 1"),
+            Err(vec![CompilationErrorKind::UnterminatedMacro {
+                name: String::from("a")
+            }])
+        );
+        assert_eq!(
+            kind(".endm"),
+            Err(vec![CompilationErrorKind::UnexpectedEndMacro])
+        );
+        assert_eq!(
+            kind(".macro add\n.endm"),
+            Err(vec![CompilationErrorKind::InvalidMacro {
+                name: String::from("add")
+            }])
+        );
+        assert_eq!(
+            kind(".macro inc r\n.endm"),
+            Err(vec![CompilationErrorKind::InvalidMacro {
+                name: String::from("inc")
+            }])
+        );
+        assert_eq!(
+            kind(".macro a\n.endm\n.macro a\n.endm\n.macro c x\n.endm\nc\n.endm"),
+            Err(vec![
+                CompilationErrorKind::DuplicateMacro {
+                    name: String::from("a")
+                },
+                CompilationErrorKind::MacroArgumentsMismatch {
+                    expected: 1,
+                    found: 0
+                },
+                CompilationErrorKind::UnexpectedEndMacro,
+            ])
+        );
+    }
+}
@@ -1,146 +1,291 @@
 mod ast;
 mod compilation_error;
 mod compilation_error_kind;
+mod data;
+mod macros;
+mod pseudo;
+mod scopes;
+mod symbols;
 mod syntax_token;
 
-use crate::parser::{ArgumentToken, Token, TokenKind};
+use crate::{
+    lints::{self, LintConfig, Warning},
+    parser::{self, ArgumentToken, Token, TokenKind},
+};
 use nano_risc_arch::{
-    Argument, Assembly, DebugInfo, Instruction, Limits, Location, Operation, SourceUnit,
+    format, Assembly, DebugInfo, Instruction, Limits, Location, Operation, SourceUnit,
 };
-use std::{collections::BTreeMap, str::FromStr};
+use std::{collections::BTreeMap, mem, str::FromStr};
+use symbols::Symbols;
 
 pub use ast::Ast;
 pub use compilation_error::CompilationError;
 pub use compilation_error_kind::CompilationErrorKind;
+pub use macros::expand_macros;
+pub use pseudo::expand_pseudo_instructions;
 pub use syntax_token::SyntaxToken;
 
+/// Compiles `tokens`, every error is reported rather than just the first
+/// one.
 pub fn compile(
     unit: SourceUnit,
     tokens: Vec<Token>,
     limits: &Limits,
-) -> Result<Assembly, CompilationError> {
-    let ast = Ast::new(&tokens)?;
+) -> Result<Assembly, Vec<CompilationError>> {
+    compile_units(vec![unit], tokens, limits)
+}
+
+/// Compiles a program made of several files, e.g. parsed with
+/// [`crate::parser::parse_with_includes`].
+pub fn compile_units(
+    units: Vec<SourceUnit>,
+    tokens: Vec<Token>,
+    limits: &Limits,
+) -> Result<Assembly, Vec<CompilationError>> {
+    let (_, ast, expansions) = front_end(tokens)?;
 
-    let mut string_positions = BTreeMap::<String, usize>::new();
+    assemble(units, &ast, expansions, limits)
+}
+
+/// Compiles like [`compile_units`] and checks the program with the lints
+/// enabled in `lints`. Warnings don't prevent compilation.
+pub fn compile_with_lints(
+    units: Vec<SourceUnit>,
+    tokens: Vec<Token>,
+    limits: &Limits,
+    lints: &LintConfig,
+) -> Result<(Assembly, Vec<Warning>), Vec<CompilationError>> {
+    let (tokens, ast, expansions) = front_end(tokens)?;
+    let assembly = assemble(units, &ast, expansions, limits)?;
+    let warnings = lints::check(&tokens, &ast, &assembly, lints);
+
+    Ok((assembly, warnings))
+}
+
+/// Expands macros and pseudo-instructions, resolves label scopes and builds
+/// the AST. Returns the tokens the AST was built from, the AST and the macro
+/// invocations the tokens' locations refer to.
+fn front_end(
+    tokens: Vec<Token>,
+) -> Result<(Vec<Token>, Ast, Vec<Location>), Vec<CompilationError>> {
+    let (tokens, expansions) = expand_macros(tokens)?;
+    let expanded = |errors| sorted_errors(errors, &expansions);
+    let tokens = scopes::resolve_labels(tokens).map_err(expanded)?;
+    let tokens = expand_pseudo_instructions(tokens).map_err(expanded)?;
+    let ast = Ast::new(&tokens).map_err(expanded)?;
+
+    Ok((tokens, ast, expansions))
+}
+
+fn assemble(
+    units: Vec<SourceUnit>,
+    ast: &Ast,
+    expansions: Vec<Location>,
+    limits: &Limits,
+) -> Result<Assembly, Vec<CompilationError>> {
+    let code_section_size = nano_risc_arch::math::align_to_mult(ast.tokens.len(), 4);
+    let mut symbols = Symbols::new(ast, limits, code_section_size);
     let mut source_loc = BTreeMap::<usize, Location>::new();
-    let mut assembly = Assembly {
-        instructions: Vec::new(),
-        debug_info: None,
-        code_section_size: nano_risc_arch::math::align_to_mult(ast.tokens.len(), 4),
-        text_section: Vec::new(),
-    };
+    let mut instructions = Vec::new();
+    let mut errors = Vec::new();
 
-    for (address, syntax) in ast.tokens.into_iter().enumerate() {
-        let instruction = match syntax.token.kind {
-            TokenKind::Operation { operation } => {
-                let operation = Operation::from_str(&operation).map_err(|_| {
-                    CompilationError::new(
-                        format!("Invalid operation: {operation}"),
-                        syntax.token.location,
-                        CompilationErrorKind::InvalidOperation,
-                    )
-                })?;
-                let arguments: Result<Vec<Argument>, CompilationError> = syntax
-                    .child
-                    .into_iter()
-                    .map(|arg| {
-                        let TokenKind::Argument { argument: arg } = arg.token.kind else {
-                            panic!("Arguments should be after an operation")
-                        };
-
-                        let arg = match arg {
-                            ArgumentToken::Register { register } => Argument::Register { register },
-                            ArgumentToken::Int { value } => Argument::Int { value },
-                            ArgumentToken::Float { value } => Argument::Float { value },
-                            ArgumentToken::String { value } => {
-                                if string_positions.contains_key(&value) {
-                                    Argument::Int {
-                                        value: string_positions[&value] as i32,
-                                    }
-                                } else {
-                                    let position = (assembly.text_section.len()
-                                        + assembly.code_section_size)
-                                        as i32;
-
-                                    if position == i32::MAX {
-                                        return Err(CompilationError::new(
-                                            format!(
-                                                "Assembly' text section is too large: {position}"
-                                            ),
-                                            syntax.token.location,
-                                            CompilationErrorKind::TooLargeAssembly {
-                                                size: position as usize,
-                                            },
-                                        ));
-                                    }
-
-                                    string_positions.insert(value.clone(), position as usize);
-                                    assembly.text_section.append(&mut value.into_bytes());
-                                    assembly.text_section.push(0);
-
-                                    Argument::Int { value: position }
-                                }
-                            }
-                            ArgumentToken::Label { name } => {
-                                if !ast.labels.contains_key(&name) {
-                                    return Err(CompilationError::new(
-                                        format!("Unknown label: {name}"),
-                                        syntax.token.location,
-                                        CompilationErrorKind::UnknownLabel { name },
-                                    ));
-                                }
+    data::emit_data(ast, &mut symbols, &mut errors);
+    symbols.resolve_constants(&mut errors);
 
-                                Argument::Int {
-                                    value: ast.labels[&name] as i32,
-                                }
-                            }
-                            ArgumentToken::Constant { name } => match name.as_str() {
-                                "data" => Argument::Int {
-                                    value: assembly.code_section_size as i32,
-                                },
-                                "ram_end" => Argument::Int {
-                                    value: limits.ram_length as i32,
-                                },
-                                _ => {
-                                    return Err(CompilationError::new(
-                                        format!("Unknown constant: {name}"),
-                                        syntax.token.location,
-                                        CompilationErrorKind::UnknownConstant { name },
-                                    ))
-                                }
-                            },
-                            ArgumentToken::Pin { id } => Argument::Pin { id },
-                        };
+    for (address, syntax) in ast.tokens.iter().enumerate() {
+        let location = syntax.token.location;
+        let TokenKind::Operation { operation } = &syntax.token.kind else {
+            panic!("Only operations should be on top level")
+        };
+        let operation = match Operation::from_str(operation) {
+            Ok(operation) => operation,
+            Err(_) => {
+                errors.push(CompilationError::new(
+                    format!("Invalid operation: {operation}"),
+                    location,
+                    CompilationErrorKind::InvalidOperation,
+                ));
+                continue;
+            }
+        };
+
+        if operation == Operation::Dbgf {
+            if let Err(error) = check_format(ast, &units, location, &syntax.child) {
+                errors.push(error);
+            }
+        }
 
-                        Ok(arg)
-                    })
-                    .collect();
+        let mut arguments = Vec::with_capacity(syntax.child.len());
+        let mut resolved = true;
 
-                Instruction {
-                    operation,
-                    arguments: arguments?,
+        for arg in &syntax.child {
+            let TokenKind::Argument { argument } = &arg.token.kind else {
+                panic!("Arguments should be after an operation")
+            };
+
+            match symbols.argument(argument, arg.token.location) {
+                Ok(argument) => arguments.push(argument),
+                Err(error) => {
+                    errors.push(error);
+                    resolved = false;
                 }
             }
-            _ => panic!("Only operations should be on top level"),
+        }
+
+        let instruction = Instruction {
+            operation,
+            arguments,
         };
 
-        source_loc.insert(address, syntax.token.location);
-        assembly.instructions.push(instruction);
+        if resolved {
+            if let Err(error) = instruction.validate(limits) {
+                let location = error
+                    .argument()
+                    .and_then(|index| syntax.child.get(index))
+                    .map_or(location, |arg| arg.token.location);
 
-        let size = assembly.instructions.len();
+                errors.push(CompilationError::new(
+                    error.message().to_string(),
+                    location,
+                    CompilationErrorKind::InvalidInstruction {
+                        kind: error.kind().clone(),
+                    },
+                ));
+            }
+        }
+
+        source_loc.insert(address, location);
+        instructions.push(instruction);
+
+        let size = address + 1;
 
-        if size + assembly.text_section.len() >= limits.ram_length {
-            return Err(CompilationError::new(
+        if size + symbols.text_section.len() >= limits.ram_length {
+            errors.push(CompilationError::new(
                 format!("Assembly is too large to be fitted into RAM: {size}"),
                 Location::default(),
                 CompilationErrorKind::TooLargeAssembly { size },
             ));
+            break;
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(sorted_errors(errors, &expansions));
+    }
+
+    let constants = mem::take(&mut symbols.constants);
+    let text_section = mem::take(&mut symbols.text_section);
+
+    Ok(Assembly {
+        instructions,
+        code_section_size,
+        text_section,
+        debug_info: Some(DebugInfo {
+            source_loc,
+            units,
+            labels: ast.labels.clone(),
+            constants,
+            expansions,
+        }),
+    })
+}
+
+/// Orders errors by their position in the source and drops the ones
+/// reported more than once, e.g. for a broken constant used in many places.
+/// Errors in macro bodies get the invocations they were expanded from.
+fn sorted_errors(errors: Vec<CompilationError>, expansions: &[Location]) -> Vec<CompilationError> {
+    let mut unique: Vec<CompilationError> = Vec::with_capacity(errors.len());
+
+    for error in errors {
+        let error = error.in_expansions(expansions);
+
+        if !unique.contains(&error) {
+            unique.push(error);
         }
     }
 
-    assembly.debug_info = Some(DebugInfo { source_loc, unit });
+    unique.sort_by_key(|error| {
+        let location = error.location().root(expansions);
+
+        (location.unit, location.offset)
+    });
+
+    unique
+}
+
+/// Checks that `dbgf`'s format string has as many placeholders as there are
+/// arguments after it. The format must be a string literal, possibly
+/// through constants, to be checked.
+fn check_format(
+    ast: &Ast,
+    units: &[SourceUnit],
+    location: Location,
+    args: &[SyntaxToken],
+) -> Result<(), CompilationError> {
+    let Some(first) = args.first() else {
+        return Ok(());
+    };
+    let mut format = &first.token;
+    let mut visited = Vec::new();
+
+    while let TokenKind::Argument {
+        argument: ArgumentToken::Constant { name },
+    } = &format.kind
+    {
+        let Some(value) = ast
+            .constants
+            .get(name)
+            .filter(|_| !visited.contains(&name))
+            .and_then(|definition| definition.child.first())
+        else {
+            // Unknown and cyclic constants are reported when resolved.
+            return Ok(());
+        };
+
+        visited.push(name);
+        format = &value.token;
+    }
+
+    let Token {
+        location: format_location,
+        kind: TokenKind::Argument {
+            argument: ArgumentToken::String { value },
+        },
+    } = format
+    else {
+        return Err(CompilationError::new(
+            String::from("dbgf's format must be a string literal or a constant holding one"),
+            first.token.location,
+            CompilationErrorKind::InvalidFormat,
+        ));
+    };
+
+    let segments = format::parse(value).map_err(|err| {
+        let unit = &units[format_location.unit];
+        let offset = parser::string_offset(unit, format_location.offset, err.offset);
+
+        CompilationError::new(
+            format!("Invalid format string: {}", err.message),
+            Location {
+                column: format_location.column + offset,
+                offset: format_location.offset + offset,
+                ..*format_location
+            },
+            CompilationErrorKind::InvalidFormat,
+        )
+    })?;
+    let expected = format::placeholders_count(&segments);
+    let found = args.len() - 1;
 
-    Ok(assembly)
+    if expected != found {
+        return Err(CompilationError::new(
+            format!("Format string expects {expected} arguments but got {found}"),
+            location,
+            CompilationErrorKind::FormatArgumentsMismatch { expected, found },
+        ));
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -172,6 +317,10 @@ mod tests {
         source_loc.insert(1, Location::new(4, 13, 56));
         source_loc.insert(2, Location::new(5, 13, 78));
 
+        let mut labels = BTreeMap::new();
+
+        labels.insert(String::from("start"), 1);
+
         assert_eq!(
             assembly,
             Ok(Assembly {
@@ -206,7 +355,13 @@ mod tests {
                         arguments: vec![Argument::Int { value: 1 },]
                     }
                 ],
-                debug_info: Some(DebugInfo { source_loc, unit }),
+                debug_info: Some(DebugInfo {
+                    source_loc,
+                    units: vec![unit],
+                    labels,
+                    constants: BTreeMap::new(),
+                    expansions: Vec::new(),
+                }),
                 text_section: Vec::new(),
                 code_section_size: 4
             })
@@ -0,0 +1,238 @@
+use nano_risc_arch::{AssemblyErrorKind, Location, RegisterKind, RegisterMode};
+
+use super::{CompilationError, CompilationErrorKind};
+use crate::parser::{ArgumentToken, Token, TokenKind};
+
+/// Pseudo-instructions and the number of arguments they take.
+const PSEUDO_INSTRUCTIONS: [(&str, usize); 9] = [
+    ("nop", 0),
+    ("inc", 1),
+    ("dec", 1),
+    ("neg", 1),
+    ("not", 1),
+    ("clr", 1),
+    ("swap", 2),
+    ("b", 1),
+    ("bl", 1),
+];
+
+/// Whether `name` is a pseudo-instruction, macros can't use these names as
+/// they're expanded first.
+pub(super) fn is_pseudo_instruction(name: &str) -> bool {
+    PSEUDO_INSTRUCTIONS
+        .iter()
+        .any(|(pseudo, _)| *pseudo == name)
+}
+
+/// Replaces pseudo-instructions in `tokens` with the operations they stand
+/// for:
+///
+/// ```text
+/// nop            mov $r0 $r0
+/// inc $r1        add $r1 $r1 1
+/// dec $r1        sub $r1 $r1 1
+/// neg $r1        sub $r1 0 $r1
+/// not $r1        xori $r1 $r1 -1
+/// clr $r1        mov $r1 0
+/// swap $r1 $r2   push $r1; push $r2; pop $r1; pop $r2
+/// b label        jmp label
+/// bl label       call label
+/// ```
+///
+/// Expanded operations keep the pseudo-instruction's location, so the debug
+/// info maps them back to its line.
+pub fn expand_pseudo_instructions(tokens: Vec<Token>) -> Result<Vec<Token>, Vec<CompilationError>> {
+    let mut output = Vec::with_capacity(tokens.len());
+    let mut errors = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        let TokenKind::Operation { operation } = &token.kind else {
+            output.push(token);
+            continue;
+        };
+        let Some((name, count)) = PSEUDO_INSTRUCTIONS
+            .iter()
+            .find(|(name, _)| name == operation)
+        else {
+            output.push(token);
+            continue;
+        };
+        let mut arguments = Vec::new();
+
+        while let Some(argument) =
+            tokens.next_if(|token| matches!(token.kind, TokenKind::Argument { .. }))
+        {
+            arguments.push(argument);
+        }
+
+        if arguments.len() != *count {
+            let message = match count {
+                0 => format!("{name} does not accept arguments"),
+                1 => format!("{name} requires 1 argument"),
+                _ => format!("{name} requires {count} arguments"),
+            };
+
+            errors.push(CompilationError::new(
+                message,
+                token.location,
+                CompilationErrorKind::InvalidInstruction {
+                    kind: AssemblyErrorKind::InvalidInstruction {
+                        name: name.to_string(),
+                    },
+                },
+            ));
+            continue;
+        }
+
+        if !matches!(*name, "nop" | "b" | "bl") {
+            if let Some(argument) = arguments.iter().find(|argument| !is_register(argument)) {
+                errors.push(CompilationError::new(
+                    format!("{name} accepts only registers"),
+                    argument.location,
+                    CompilationErrorKind::InvalidInstruction {
+                        kind: AssemblyErrorKind::InvalidInstruction {
+                            name: name.to_string(),
+                        },
+                    },
+                ));
+                continue;
+            }
+        }
+
+        output.extend(lower(name, token.location, arguments));
+    }
+
+    if errors.is_empty() {
+        Ok(output)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Constants may stand for registers, the compiler checks them once they're
+/// resolved.
+fn is_register(token: &Token) -> bool {
+    matches!(
+        token.kind,
+        TokenKind::Argument {
+            argument: ArgumentToken::Register { .. } | ArgumentToken::Constant { .. }
+        }
+    )
+}
+
+fn lower(name: &str, location: Location, arguments: Vec<Token>) -> Vec<Token> {
+    let operation = |name: &str| Token {
+        location,
+        kind: TokenKind::Operation {
+            operation: name.to_string(),
+        },
+    };
+    let literal = |argument: ArgumentToken| Token {
+        location,
+        kind: TokenKind::Argument { argument },
+    };
+    let int = |value: i32| literal(ArgumentToken::Int { value });
+
+    match (name, arguments.as_slice()) {
+        ("nop", []) => {
+            let r0 = ArgumentToken::Register {
+                register: RegisterKind::Regular {
+                    id: 0,
+                    mode: RegisterMode::Direct,
+                },
+            };
+
+            vec![operation("mov"), literal(r0.clone()), literal(r0)]
+        }
+        ("inc", [register]) => vec![operation("add"), register.clone(), register.clone(), int(1)],
+        ("dec", [register]) => vec![operation("sub"), register.clone(), register.clone(), int(1)],
+        ("neg", [register]) => vec![operation("sub"), register.clone(), int(0), register.clone()],
+        ("not", [register]) => vec![
+            operation("xori"),
+            register.clone(),
+            register.clone(),
+            int(-1),
+        ],
+        ("clr", [register]) => vec![operation("mov"), register.clone(), int(0)],
+        ("swap", [a, b]) => vec![
+            operation("push"),
+            a.clone(),
+            operation("push"),
+            b.clone(),
+            operation("pop"),
+            a.clone(),
+            operation("pop"),
+            b.clone(),
+        ],
+        ("b", [target]) => vec![operation("jmp"), target.clone()],
+        ("bl", [target]) => vec![operation("call"), target.clone()],
+        _ => unreachable!("Arguments are checked against PSEUDO_INSTRUCTIONS"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nano_risc_arch::SourceUnit;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::parser;
+
+    fn expand(source: &str) -> Result<Vec<Token>, Vec<CompilationError>> {
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+
+        expand_pseudo_instructions(parser::parse(&unit).unwrap())
+    }
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        expand(source)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    #[test]
+    fn lowering() {
+        assert_eq!(kinds("nop"), kinds("mov $r0 $r0"));
+        assert_eq!(kinds("inc $r1"), kinds("add $r1 $r1 1"));
+        assert_eq!(kinds("dec $r1"), kinds("sub $r1 $r1 1"));
+        assert_eq!(kinds("neg $r1"), kinds("sub $r1 0 $r1"));
+        assert_eq!(kinds("not $r1"), kinds("xori $r1 $r1 -1"));
+        assert_eq!(kinds("clr $r1"), kinds("mov $r1 0"));
+        assert_eq!(
+            kinds("swap $r1 $r2"),
+            kinds("push $r1\npush $r2\npop $r1\npop $r2")
+        );
+        assert_eq!(kinds("b end"), kinds("jmp end"));
+        assert_eq!(kinds("bl end"), kinds("call end"));
+    }
+
+    #[test]
+    fn locations() {
+        let tokens = expand("halt\nswap $r1 $r2").unwrap();
+
+        assert!(tokens[1..].iter().all(|token| token.location.line == 2));
+    }
+
+    #[test]
+    fn errors() {
+        let errors = |source: &str| {
+            expand(source)
+                .unwrap_err()
+                .iter()
+                .map(|error| (error.location().column, error.message().to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            errors("inc\nswap $r0 5\nnop 1"),
+            vec![
+                (1, String::from("inc requires 1 argument")),
+                (10, String::from("swap accepts only registers")),
+                (1, String::from("nop does not accept arguments")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+
+use super::{CompilationError, CompilationErrorKind};
+use crate::parser::{ArgumentToken, ExpressionToken, Token, TokenKind};
+
+/// Gives local and anonymous labels unique names:
+///
+/// ```text
+/// fact:           fact:
+/// @loop:          fact@loop:
+///     jmp @loop       jmp fact@loop
+/// 1:              1#1:
+///     jmp 1b          jmp 1#1
+///     jmp 1f          jmp 1#2
+/// 1:              1#2:
+/// ```
+///
+/// A local label belongs to the global label before it, so every function
+/// can have its own `@loop`. Anonymous labels are numbers referenced as the
+/// next (`1f`) or the previous (`1b`) label with that number.
+pub(crate) fn resolve_labels(tokens: Vec<Token>) -> Result<Vec<Token>, Vec<CompilationError>> {
+    let mut anonymous: BTreeMap<&str, Vec<(usize, String)>> = BTreeMap::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        if let TokenKind::Label { name } = &token.kind {
+            if is_anonymous(name) {
+                let definitions = anonymous.entry(name).or_default();
+
+                definitions.push((index, format!("{name}#{}", definitions.len() + 1)));
+            }
+        }
+    }
+
+    let mut output = Vec::with_capacity(tokens.len());
+    let mut errors = Vec::new();
+    let mut scope = "";
+    let mut expanded_scope: Option<(usize, &str)> = None;
+
+    for (index, token) in tokens.iter().enumerate() {
+        // A global label in a macro body only scopes the rest of its own
+        // expansion, the code after the invocation keeps the caller's scope.
+        let current = match (token.location.expansion, expanded_scope) {
+            (Some(expansion), Some((id, name))) if expansion == id => name,
+            _ => scope,
+        };
+        let kind = match &token.kind {
+            TokenKind::Label { name } if is_anonymous(name) => {
+                let (_, unique) = anonymous[name.as_str()]
+                    .iter()
+                    .find(|(definition, _)| *definition == index)
+                    .unwrap();
+
+                TokenKind::Label {
+                    name: unique.clone(),
+                }
+            }
+            TokenKind::Label { name } if name.starts_with('@') => TokenKind::Label {
+                name: format!("{current}{name}"),
+            },
+            TokenKind::Label { name } => {
+                match token.location.expansion {
+                    Some(expansion) => expanded_scope = Some((expansion, name)),
+                    None => scope = name,
+                }
+
+                token.kind.clone()
+            }
+            TokenKind::Argument {
+                argument: ArgumentToken::Label { name },
+            } if is_anonymous_reference(name) => {
+                let (number, direction) = name.split_at(name.len() - 1);
+                let definitions = anonymous.get(number).map_or(&[][..], Vec::as_slice);
+                let definition = if direction == "f" {
+                    definitions
+                        .iter()
+                        .find(|(definition, _)| *definition > index)
+                } else {
+                    definitions
+                        .iter()
+                        .rev()
+                        .find(|(definition, _)| *definition < index)
+                };
+
+                match definition {
+                    Some((_, unique)) => TokenKind::Argument {
+                        argument: ArgumentToken::Label {
+                            name: unique.clone(),
+                        },
+                    },
+                    None => {
+                        let position = if direction == "f" { "after" } else { "before" };
+
+                        errors.push(CompilationError::new(
+                            format!("No anonymous label {number} {position} this reference"),
+                            token.location,
+                            CompilationErrorKind::UnknownLabel { name: name.clone() },
+                        ));
+                        token.kind.clone()
+                    }
+                }
+            }
+            TokenKind::Argument {
+                argument: ArgumentToken::Label { name },
+            } if name.starts_with('@') => TokenKind::Argument {
+                argument: ArgumentToken::Label {
+                    name: format!("{current}{name}"),
+                },
+            },
+            TokenKind::Argument {
+                argument: ArgumentToken::Expression { expression },
+            } => TokenKind::Argument {
+                argument: ArgumentToken::Expression {
+                    expression: expression.map_labels(&|name: &String| ExpressionToken::Label {
+                        name: if name.starts_with('@') {
+                            format!("{current}{name}")
+                        } else {
+                            name.clone()
+                        },
+                    }),
+                },
+            },
+            kind => kind.clone(),
+        };
+
+        output.push(Token {
+            location: token.location,
+            kind,
+        });
+    }
+
+    if errors.is_empty() {
+        Ok(output)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Anonymous labels are named with digits only, e.g. `1:`.
+pub(super) fn is_anonymous(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+fn is_anonymous_reference(name: &str) -> bool {
+    match name.strip_suffix(['f', 'b']) {
+        Some(number) => is_anonymous(number),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nano_risc_arch::SourceUnit;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::parser;
+
+    fn names(source: &str) -> Result<Vec<String>, Vec<CompilationError>> {
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = resolve_labels(parser::parse(&unit).unwrap())?;
+
+        Ok(tokens
+            .into_iter()
+            .filter_map(|token| match token.kind {
+                TokenKind::Label { name }
+                | TokenKind::Argument {
+                    argument: ArgumentToken::Label { name },
+                } => Some(name),
+                _ => None,
+            })
+            .collect())
+    }
+
+    #[test]
+    fn local() {
+        assert_eq!(
+            names("@start:\na:\n@loop:\njmp @loop\nb:\n@loop:\njmp @loop\njmp a"),
+            Ok(vec![
+                String::from("@start"),
+                String::from("a"),
+                String::from("a@loop"),
+                String::from("a@loop"),
+                String::from("b"),
+                String::from("b@loop"),
+                String::from("b@loop"),
+                String::from("a"),
+            ])
+        );
+    }
+
+    #[test]
+    fn anonymous() {
+        assert_eq!(
+            names("1:\njmp 1f\njmp 1b\n1:\n2:\njmp 1b"),
+            Ok(vec![
+                String::from("1#1"),
+                String::from("1#2"),
+                String::from("1#1"),
+                String::from("1#2"),
+                String::from("2#1"),
+                String::from("1#2"),
+            ])
+        );
+
+        let errors = names("jmp 1b\n1:\njmp 1f").unwrap_err();
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| error.message())
+                .collect::<Vec<_>>(),
+            vec![
+                "No anonymous label 1 before this reference",
+                "No anonymous label 1 after this reference",
+            ]
+        );
+    }
+}
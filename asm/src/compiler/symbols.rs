@@ -0,0 +1,262 @@
+use std::collections::BTreeMap;
+
+use nano_risc_arch::{Argument, Limits, Location};
+
+use super::{Ast, CompilationError, CompilationErrorKind};
+use crate::parser::{ArgumentToken, BinaryOperator, ExpressionToken, TokenKind};
+
+/// Constants that are always defined.
+pub(crate) const BUILTIN_CONSTANTS: [&str; 2] = ["data", "ram_end"];
+
+/// Turns argument tokens into arguments by resolving labels, constants and
+/// strings. Strings are placed into the text section on their first use.
+pub(crate) struct Symbols<'a> {
+    ast: &'a Ast,
+    limits: &'a Limits,
+    code_section_size: usize,
+    pub text_section: Vec<u8>,
+    strings: BTreeMap<String, usize>,
+    pub constants: BTreeMap<String, Argument>,
+    /// Addresses of the labels pointing to data directives.
+    pub data_labels: BTreeMap<String, usize>,
+    /// Constants being resolved, to detect definitions referring to
+    /// themselves.
+    resolving: Vec<String>,
+}
+
+impl<'a> Symbols<'a> {
+    pub fn new(ast: &'a Ast, limits: &'a Limits, code_section_size: usize) -> Self {
+        Self {
+            ast,
+            limits,
+            code_section_size,
+            text_section: Vec::new(),
+            strings: BTreeMap::new(),
+            constants: BTreeMap::new(),
+            data_labels: BTreeMap::new(),
+            resolving: Vec::new(),
+        }
+    }
+
+    /// Resolves every constant defined in the program.
+    pub fn resolve_constants(&mut self, errors: &mut Vec<CompilationError>) {
+        for (name, definition) in &self.ast.constants {
+            if let Err(error) = self.constant(name, definition.token.location) {
+                errors.push(error);
+            }
+        }
+    }
+
+    pub fn argument(
+        &mut self,
+        argument: &ArgumentToken,
+        location: Location,
+    ) -> Result<Argument, CompilationError> {
+        let argument = match argument {
+            ArgumentToken::Register { register } => Argument::Register {
+                register: *register,
+            },
+            ArgumentToken::Int { value } => Argument::Int { value: *value },
+            ArgumentToken::Float { value } => Argument::Float { value: *value },
+            ArgumentToken::String { value } => Argument::Int {
+                value: self.string(value, location)? as i32,
+            },
+            ArgumentToken::Label { name } => {
+                let Some(address) = self
+                    .ast
+                    .labels
+                    .get(name)
+                    .or_else(|| self.data_labels.get(name))
+                else {
+                    return Err(CompilationError::new(
+                        format!("Unknown label: {name}"),
+                        location,
+                        CompilationErrorKind::UnknownLabel { name: name.clone() },
+                    ));
+                };
+
+                Argument::Int {
+                    value: *address as i32,
+                }
+            }
+            ArgumentToken::Constant { name } => self.constant(name, location)?,
+            ArgumentToken::Pin { id } => Argument::Pin { id: *id },
+            ArgumentToken::Expression { expression } => Argument::Int {
+                value: self.expression(expression, location)?,
+            },
+        };
+
+        Ok(argument)
+    }
+
+    fn expression(
+        &mut self,
+        expression: &ExpressionToken,
+        location: Location,
+    ) -> Result<i32, CompilationError> {
+        match expression {
+            ExpressionToken::Int { value } => Ok(*value),
+            ExpressionToken::Label { name } => {
+                let argument = ArgumentToken::Label { name: name.clone() };
+
+                self.integer(&argument, name, location)
+            }
+            ExpressionToken::Constant { name } => {
+                let argument = ArgumentToken::Constant { name: name.clone() };
+
+                self.integer(&argument, name, location)
+            }
+            ExpressionToken::Unary { operator, operand } => {
+                let value = self.expression(operand, location)?;
+
+                operator.apply(value).ok_or_else(|| {
+                    CompilationError::new(
+                        format!("Expression overflows: {operator}{value}"),
+                        location,
+                        CompilationErrorKind::ExpressionOverflow,
+                    )
+                })
+            }
+            ExpressionToken::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                let left = self.expression(left, location)?;
+                let right = self.expression(right, location)?;
+
+                if right == 0
+                    && matches!(operator, BinaryOperator::Divide | BinaryOperator::Remainder)
+                {
+                    return Err(CompilationError::new(
+                        format!("Division by zero in expression: {left} {operator} {right}"),
+                        location,
+                        CompilationErrorKind::InvalidExpression,
+                    ));
+                }
+
+                operator.apply(left, right).ok_or_else(|| {
+                    CompilationError::new(
+                        format!("Expression overflows: {left} {operator} {right}"),
+                        location,
+                        CompilationErrorKind::ExpressionOverflow,
+                    )
+                })
+            }
+        }
+    }
+
+    /// Resolves a label or constant used as an operand of an expression.
+    fn integer(
+        &mut self,
+        argument: &ArgumentToken,
+        name: &str,
+        location: Location,
+    ) -> Result<i32, CompilationError> {
+        match self.argument(argument, location)? {
+            Argument::Int { value } => Ok(value),
+            _ => Err(CompilationError::new(
+                format!("{name} is not an integer and can't be used in an expression"),
+                location,
+                CompilationErrorKind::InvalidExpression,
+            )),
+        }
+    }
+
+    fn constant(&mut self, name: &str, location: Location) -> Result<Argument, CompilationError> {
+        match name {
+            "data" => {
+                return Ok(Argument::Int {
+                    value: self.code_section_size as i32,
+                })
+            }
+            "ram_end" => {
+                return Ok(Argument::Int {
+                    value: self.limits.ram_length as i32,
+                })
+            }
+            _ => {}
+        }
+
+        if let Some(value) = self.constants.get(name) {
+            return Ok(*value);
+        }
+
+        let Some(definition) = self.ast.constants.get(name) else {
+            return Err(CompilationError::new(
+                format!("Unknown constant: {name}"),
+                location,
+                CompilationErrorKind::UnknownConstant {
+                    name: name.to_string(),
+                },
+            ));
+        };
+
+        if self.resolving.iter().any(|resolving| resolving == name) {
+            return Err(CompilationError::new(
+                format!("Constant {name} is defined through itself"),
+                location,
+                CompilationErrorKind::InvalidConstant {
+                    name: name.to_string(),
+                },
+            ));
+        }
+
+        let [value] = definition.child.as_slice() else {
+            unreachable!("Ast checks that constants have 1 value");
+        };
+        let TokenKind::Argument { argument } = &value.token.kind else {
+            unreachable!("Constants' values are arguments");
+        };
+
+        self.resolving.push(name.to_string());
+
+        let resolved = self.argument(argument, value.token.location);
+
+        self.resolving.pop();
+
+        let resolved = resolved?;
+
+        if !matches!(resolved, Argument::Int { .. } | Argument::Float { .. }) {
+            return Err(CompilationError::new(
+                format!("Constant {name} must be a number, a string or a label"),
+                value.token.location,
+                CompilationErrorKind::InvalidConstant {
+                    name: name.to_string(),
+                },
+            ));
+        }
+
+        self.constants.insert(name.to_string(), resolved);
+
+        Ok(resolved)
+    }
+
+    /// Returns the address of `offset` in the text section.
+    pub fn text_address(&self, offset: usize) -> usize {
+        self.code_section_size + offset
+    }
+
+    /// Returns the address of `value` in the text section.
+    fn string(&mut self, value: &str, location: Location) -> Result<usize, CompilationError> {
+        if let Some(position) = self.strings.get(value) {
+            return Ok(*position);
+        }
+
+        let position = self.text_address(self.text_section.len());
+
+        if position >= i32::MAX as usize {
+            return Err(CompilationError::new(
+                format!("Assembly' text section is too large: {position}"),
+                location,
+                CompilationErrorKind::TooLargeAssembly { size: position },
+            ));
+        }
+
+        self.strings.insert(value.to_string(), position);
+        self.text_section.extend_from_slice(value.as_bytes());
+        self.text_section.push(0);
+
+        Ok(position)
+    }
+}
@@ -0,0 +1,102 @@
+use nano_risc_arch::{AssemblyError, Location};
+
+use super::{Note, Severity};
+use crate::{compiler::CompilationError, lints::Warning, parser::ParsingError, stack::StackIssue};
+
+/// An error ready to be shown to a user with [`super::Renderer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub location: Option<Location>,
+    pub notes: Vec<Note>,
+}
+
+impl Diagnostic {
+    /// Creates an error diagnostic.
+    pub fn new(message: String, location: Option<Location>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+            location,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Adds a note pointing to every macro invocation the location was
+    /// expanded from, e.g. with [`nano_risc_arch::DebugInfo::expansions`].
+    pub fn with_expansions(self, expansions: &[Location]) -> Self {
+        let invocations = self
+            .location
+            .into_iter()
+            .flat_map(|location| location.invocations(expansions));
+
+        self.with_invocations(invocations)
+    }
+
+    fn with_invocations(mut self, invocations: impl IntoIterator<Item = Location>) -> Self {
+        for location in invocations {
+            self.notes.push(Note {
+                message: String::from("in this macro invocation"),
+                location: Some(location),
+            });
+        }
+
+        self
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_note(mut self, message: String, location: Option<Location>) -> Self {
+        self.notes.push(Note { message, location });
+        self
+    }
+}
+
+impl From<&ParsingError> for Diagnostic {
+    fn from(error: &ParsingError) -> Self {
+        let mut diagnostic = Self::new(error.message().to_string(), Some(error.location()));
+        let mut inner = error.inner();
+
+        while let Some(error) = inner {
+            if Some(error.location()) != diagnostic.location {
+                diagnostic =
+                    diagnostic.with_note(error.message().to_string(), Some(error.location()));
+            }
+
+            inner = error.inner();
+        }
+
+        diagnostic
+    }
+}
+
+impl From<&CompilationError> for Diagnostic {
+    fn from(error: &CompilationError) -> Self {
+        Self::new(error.message().to_string(), Some(error.location()))
+            .with_invocations(error.expanded_from().iter().copied())
+    }
+}
+
+impl From<&AssemblyError> for Diagnostic {
+    fn from(error: &AssemblyError) -> Self {
+        Self::new(error.message().to_string(), error.location())
+    }
+}
+
+impl From<&Warning> for Diagnostic {
+    fn from(warning: &Warning) -> Self {
+        Self::new(warning.message().to_string(), Some(warning.location()))
+            .with_severity(Severity::Warning)
+            .with_note(format!("reported by the {} lint", warning.lint()), None)
+    }
+}
+
+impl From<&StackIssue> for Diagnostic {
+    fn from(issue: &StackIssue) -> Self {
+        Self::new(issue.message().to_string(), issue.location()).with_severity(Severity::Warning)
+    }
+}
@@ -0,0 +1,9 @@
+mod diagnostic;
+mod note;
+mod renderer;
+mod severity;
+
+pub use diagnostic::Diagnostic;
+pub use note::Note;
+pub use renderer::Renderer;
+pub use severity::Severity;
@@ -0,0 +1,9 @@
+use nano_risc_arch::Location;
+
+/// Additional information attached to a [`super::Diagnostic`], e.g. where a
+/// macro was invoked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    pub message: String,
+    pub location: Option<Location>,
+}
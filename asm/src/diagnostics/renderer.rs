@@ -0,0 +1,196 @@
+use std::fmt::Write;
+
+use nano_risc_arch::{Location, SourceUnit};
+
+use super::{Diagnostic, Severity};
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const CYAN: &str = "\x1b[1;36m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders diagnostics with the source line they point to:
+///
+/// ```text
+/// error: Unknown label: loop
+///  --> main.asm:3:5
+///   |
+/// 3 | jmp loop
+///   |     ^^^^
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Renderer {
+    colored: bool,
+}
+
+impl Renderer {
+    /// Renders with ANSI colors for terminals.
+    pub fn colored() -> Self {
+        Self { colored: true }
+    }
+
+    pub fn plain() -> Self {
+        Self { colored: false }
+    }
+
+    /// Renders `diagnostic`, `units` are the files its locations point into
+    /// with [`Location::unit`].
+    pub fn render(&self, diagnostic: &Diagnostic, units: &[SourceUnit]) -> String {
+        let mut output = String::new();
+        let color = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        writeln!(
+            output,
+            "{}{}{}: {}{}",
+            self.paint(color),
+            diagnostic.severity,
+            self.paint(BOLD),
+            diagnostic.message,
+            self.paint(RESET)
+        )
+        .unwrap();
+
+        if let Some(location) = diagnostic.location {
+            self.snippet(&mut output, location, units, color);
+        }
+
+        for note in &diagnostic.notes {
+            writeln!(
+                output,
+                "{}note{}: {}",
+                self.paint(CYAN),
+                self.paint(RESET),
+                note.message
+            )
+            .unwrap();
+
+            if let Some(location) = note.location {
+                self.snippet(&mut output, location, units, CYAN);
+            }
+        }
+
+        output
+    }
+
+    fn snippet(&self, output: &mut String, location: Location, units: &[SourceUnit], color: &str) {
+        let unit = units.get(location.unit);
+        let name = unit.map_or("<unknown>", |unit| unit.name());
+        let Some(line) = unit.and_then(|unit| unit.line(location.line)) else {
+            writeln!(
+                output,
+                " {}-->{} {name}:{}:{}",
+                self.paint(BLUE),
+                self.paint(RESET),
+                location.line,
+                location.column
+            )
+            .unwrap();
+            return;
+        };
+        let number = location.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let start = (location.column - 1).min(line.len());
+        // Whitespace is kept so tabs line up with the source line.
+        let padding: String = String::from_utf8_lossy(&line[..start])
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = line[start..]
+            .iter()
+            .take_while(|byte| !byte.is_ascii_whitespace())
+            .count()
+            .max(1);
+
+        writeln!(
+            output,
+            "{gutter}{}-->{} {name}:{}:{}",
+            self.paint(BLUE),
+            self.paint(RESET),
+            location.line,
+            location.column
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "{gutter} {}|{}",
+            self.paint(BLUE),
+            self.paint(RESET)
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "{}{number} |{} {}",
+            self.paint(BLUE),
+            self.paint(RESET),
+            String::from_utf8_lossy(line)
+        )
+        .unwrap();
+        writeln!(
+            output,
+            "{gutter} {}|{} {padding}{}{}{}",
+            self.paint(BLUE),
+            self.paint(RESET),
+            self.paint(color),
+            "^".repeat(width),
+            self.paint(RESET)
+        )
+        .unwrap();
+    }
+
+    fn paint<'a>(&self, color: &'a str) -> &'a str {
+        if self.colored {
+            color
+        } else {
+            ""
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Renderer;
+    use crate::{compiler, diagnostics::Diagnostic, parser};
+    use nano_risc_arch::{Limits, SourceUnit};
+    use pretty_assertions::assert_eq;
+
+    fn render(renderer: Renderer, source: &str) -> String {
+        let unit = SourceUnit::new(String::from("main.asm"), source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+        let errors = compiler::compile(unit.clone(), tokens, &Limits::default()).unwrap_err();
+
+        renderer.render(&Diagnostic::from(&errors[0]), &[unit])
+    }
+
+    #[test]
+    fn plain() {
+        let source = ".macro go\n\tjmp target\n.endm\nhalt\ngo\n";
+
+        assert_eq!(
+            render(Renderer::plain(), source),
+            "error: Unknown label: target
+ --> main.asm:2:6
+  |
+2 | \tjmp target
+  | \t    ^^^^^^
+note: in this macro invocation
+ --> main.asm:5:1
+  |
+5 | go
+  | ^^
+"
+        );
+    }
+
+    #[test]
+    fn colored() {
+        let rendered = render(Renderer::colored(), "jmp target");
+
+        assert!(rendered.starts_with("\x1b[1;31merror\x1b[1m: Unknown label: target\x1b[0m\n"));
+        assert!(rendered.contains("\x1b[1;31m^^^^^^\x1b[0m"));
+    }
+}
@@ -1,2 +1,5 @@
 pub mod compiler;
+pub mod diagnostics;
+pub mod lints;
 pub mod parser;
+pub mod stack;
@@ -0,0 +1,58 @@
+use std::{fmt::Display, str::FromStr};
+
+/// A check for code that assembles but is likely a mistake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Lint {
+    /// A label no argument refers to.
+    UnusedLabel,
+    /// An instruction right after `jmp`, `halt` or `ret` without a label.
+    UnreachableCode,
+    /// A branch or jump to the instruction right after it.
+    BranchToNext,
+    /// A `%rN` argument where `$rN` is never written.
+    UninitializedIndirect,
+    /// An instruction computing `$pc`, e.g. with `add` or `and`, rather than
+    /// jumping.
+    ArithmeticPcWrite,
+    /// `dbgs` with a number or a code label instead of a string.
+    NonStringDbgs,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 6] = [
+        Lint::UnusedLabel,
+        Lint::UnreachableCode,
+        Lint::BranchToNext,
+        Lint::UninitializedIndirect,
+        Lint::ArithmeticPcWrite,
+        Lint::NonStringDbgs,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedLabel => "unused-label",
+            Lint::UnreachableCode => "unreachable-code",
+            Lint::BranchToNext => "branch-to-next",
+            Lint::UninitializedIndirect => "uninitialized-indirect",
+            Lint::ArithmeticPcWrite => "arithmetic-pc-write",
+            Lint::NonStringDbgs => "non-string-dbgs",
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Lint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|lint| lint.name() == s)
+            .ok_or_else(|| format!("Unknown lint: {s}"))
+    }
+}
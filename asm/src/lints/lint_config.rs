@@ -0,0 +1,38 @@
+use std::collections::BTreeSet;
+
+use super::Lint;
+
+/// The lints to check, every lint is enabled by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintConfig {
+    enabled: BTreeSet<Lint>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Lint::ALL.into_iter().collect(),
+        }
+    }
+}
+
+impl LintConfig {
+    /// A config with every lint disabled.
+    pub fn none() -> Self {
+        Self {
+            enabled: BTreeSet::new(),
+        }
+    }
+
+    pub fn enable(&mut self, lint: Lint) {
+        self.enabled.insert(lint);
+    }
+
+    pub fn allow(&mut self, lint: Lint) {
+        self.enabled.remove(&lint);
+    }
+
+    pub fn is_enabled(&self, lint: Lint) -> bool {
+        self.enabled.contains(&lint)
+    }
+}
@@ -0,0 +1,289 @@
+mod lint;
+mod lint_config;
+mod warning;
+
+use std::collections::BTreeSet;
+
+use nano_risc_arch::{Argument, Assembly, Operation, RegisterKind, RegisterMode};
+
+use crate::{
+    compiler::Ast,
+    parser::{ArgumentToken, ExpressionToken, Token, TokenKind},
+};
+
+pub use lint::Lint;
+pub use lint_config::LintConfig;
+pub use warning::Warning;
+
+/// Labels the test runner looks for, they're used even without references.
+/// Local labels inside a test, e.g. `test_count@loop`, aren't tests.
+const TEST_PREFIX: &str = "test_";
+
+/// Runs the enabled lints over a compiled program, `tokens` are the tokens
+/// `ast` was built from after macro expansion.
+pub(crate) fn check(
+    tokens: &[Token],
+    ast: &Ast,
+    assembly: &Assembly,
+    config: &LintConfig,
+) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    if config.is_enabled(Lint::UnusedLabel) {
+        unused_labels(tokens, &mut warnings);
+    }
+
+    if config.is_enabled(Lint::UnreachableCode) {
+        unreachable_code(ast, assembly, &mut warnings);
+    }
+
+    if config.is_enabled(Lint::BranchToNext) {
+        branches_to_next(ast, assembly, &mut warnings);
+    }
+
+    if config.is_enabled(Lint::UninitializedIndirect) {
+        uninitialized_indirect(ast, assembly, &mut warnings);
+    }
+
+    if config.is_enabled(Lint::ArithmeticPcWrite) {
+        arithmetic_pc_writes(ast, assembly, &mut warnings);
+    }
+
+    if config.is_enabled(Lint::NonStringDbgs) {
+        non_string_dbgs(ast, assembly, &mut warnings);
+    }
+
+    let expansions = assembly
+        .debug_info
+        .as_ref()
+        .map_or(&[][..], |info| info.expansions.as_slice());
+
+    warnings.sort_by_key(|warning| {
+        let location = warning.location().root(expansions);
+
+        (location.unit, location.offset)
+    });
+
+    warnings
+}
+
+fn unused_labels(tokens: &[Token], warnings: &mut Vec<Warning>) {
+    let mut used = BTreeSet::new();
+
+    for token in tokens {
+        match &token.kind {
+            TokenKind::Argument {
+                argument: ArgumentToken::Label { name },
+            } => {
+                used.insert(name.as_str());
+            }
+            TokenKind::Argument {
+                argument: ArgumentToken::Expression { expression },
+            } => expression_labels(expression, &mut used),
+            _ => {}
+        }
+    }
+
+    for token in tokens {
+        let TokenKind::Label { name } = &token.kind else {
+            continue;
+        };
+
+        let is_test = name.starts_with(TEST_PREFIX) && !name.contains('@');
+
+        if !used.contains(name.as_str()) && !is_test {
+            warnings.push(Warning::new(
+                format!("Label {name} is never used"),
+                token.location,
+                Lint::UnusedLabel,
+            ));
+        }
+    }
+}
+
+fn expression_labels<'a>(expression: &'a ExpressionToken, used: &mut BTreeSet<&'a str>) {
+    match expression {
+        ExpressionToken::Label { name } => {
+            used.insert(name.as_str());
+        }
+        ExpressionToken::Unary { operand, .. } => expression_labels(operand, used),
+        ExpressionToken::Binary { left, right, .. } => {
+            expression_labels(left, used);
+            expression_labels(right, used);
+        }
+        ExpressionToken::Int { .. } | ExpressionToken::Constant { .. } => {}
+    }
+}
+
+fn unreachable_code(ast: &Ast, assembly: &Assembly, warnings: &mut Vec<Warning>) {
+    let labeled: BTreeSet<usize> = ast.labels.values().copied().collect();
+
+    for (address, pair) in assembly.instructions.windows(2).enumerate() {
+        let previous = pair[0].operation;
+
+        if matches!(previous, Operation::Jmp | Operation::Halt | Operation::Ret)
+            && !labeled.contains(&(address + 1))
+        {
+            warnings.push(Warning::new(
+                format!("Unreachable instruction after {previous}"),
+                ast.tokens[address + 1].token.location,
+                Lint::UnreachableCode,
+            ));
+        }
+    }
+}
+
+fn branches_to_next(ast: &Ast, assembly: &Assembly, warnings: &mut Vec<Warning>) {
+    for (address, instruction) in assembly.instructions.iter().enumerate() {
+        let operation = instruction.operation;
+
+        if operation != Operation::Jmp && !operation.is_conditional_branch() {
+            continue;
+        }
+
+        if let Some(Argument::Int { value }) = instruction.arguments.last() {
+            if *value as usize == address + 1 {
+                warnings.push(Warning::new(
+                    format!("{operation} goes to the next instruction, which runs anyway"),
+                    ast.tokens[address].token.location,
+                    Lint::BranchToNext,
+                ));
+            }
+        }
+    }
+}
+
+/// Registers written by an instruction through its first argument.
+fn written_registers(operation: Operation, id: usize) -> Option<std::ops::Range<usize>> {
+    if !operation.writes_first_argument() {
+        return None;
+    }
+
+    match operation {
+        Operation::Vadd { size }
+        | Operation::Vsub { size }
+        | Operation::Vscale { size }
+        | Operation::Vnorm { size } => Some(id..id + size),
+        Operation::Vcross => Some(id..id + 3),
+        _ => Some(id..id + 1),
+    }
+}
+
+fn uninitialized_indirect(ast: &Ast, assembly: &Assembly, warnings: &mut Vec<Warning>) {
+    let mut written = BTreeSet::new();
+
+    for instruction in &assembly.instructions {
+        let Some(Argument::Register {
+            register: RegisterKind::Regular { id, mode },
+        }) = instruction.arguments.first()
+        else {
+            continue;
+        };
+        let Some(registers) = written_registers(instruction.operation, *id) else {
+            continue;
+        };
+
+        // An indirect write may change any register.
+        if *mode == RegisterMode::Indirect {
+            return;
+        }
+
+        written.extend(registers);
+    }
+
+    for (address, instruction) in assembly.instructions.iter().enumerate() {
+        for argument in &instruction.arguments {
+            if let Argument::Register {
+                register:
+                    RegisterKind::Regular {
+                        id,
+                        mode: RegisterMode::Indirect,
+                    },
+            } = argument
+            {
+                if !written.contains(id) {
+                    warnings.push(Warning::new(
+                        format!("%r{id} reads $r{id}, which is never written"),
+                        ast.tokens[address].token.location,
+                        Lint::UninitializedIndirect,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn arithmetic_pc_writes(ast: &Ast, assembly: &Assembly, warnings: &mut Vec<Warning>) {
+    for (address, instruction) in assembly.instructions.iter().enumerate() {
+        let operation = instruction.operation;
+        // Moving and loading a target into `$pc` are jumps.
+        let computed = operation.writes_first_argument()
+            && !matches!(
+                operation,
+                Operation::Mov
+                    | Operation::Pop
+                    | Operation::Peek
+                    | Operation::Lb
+                    | Operation::Lh
+                    | Operation::Lw
+            );
+
+        if computed
+            && matches!(
+                instruction.arguments.first(),
+                Some(Argument::Register {
+                    register: RegisterKind::ProgramCounter
+                })
+            )
+        {
+            warnings.push(Warning::new(
+                format!("{operation} writes to $pc, use jmp or a branch instead"),
+                ast.tokens[address].token.location,
+                Lint::ArithmeticPcWrite,
+            ));
+        }
+    }
+}
+
+fn non_string_dbgs(ast: &Ast, assembly: &Assembly, warnings: &mut Vec<Warning>) {
+    let text_section =
+        assembly.code_section_size..assembly.code_section_size + assembly.text_section.len();
+    let constants = assembly.debug_info.as_ref().map(|info| &info.constants);
+
+    for (address, instruction) in assembly.instructions.iter().enumerate() {
+        if instruction.operation != Operation::Dbgs {
+            continue;
+        }
+
+        let Some(TokenKind::Argument { argument }) = ast.tokens[address]
+            .child
+            .first()
+            .map(|syntax| &syntax.token.kind)
+        else {
+            continue;
+        };
+        let string = match argument {
+            ArgumentToken::String { .. } | ArgumentToken::Register { .. } => true,
+            ArgumentToken::Label { name } => ast.data_labels.contains_key(name),
+            // Strings and data labels resolve to addresses in the text
+            // section.
+            ArgumentToken::Constant { name } => {
+                match constants.and_then(|constants| constants.get(name)) {
+                    Some(Argument::Int { value }) => {
+                        usize::try_from(*value).is_ok_and(|address| text_section.contains(&address))
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        };
+
+        if !string {
+            warnings.push(Warning::new(
+                String::from("dbgs expects a string"),
+                ast.tokens[address].token.location,
+                Lint::NonStringDbgs,
+            ));
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use nano_risc_arch::Location;
+
+use super::Lint;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    message: String,
+    location: Location,
+    lint: Lint,
+}
+
+impl Warning {
+    pub fn new(message: String, location: Location, lint: Lint) -> Self {
+        Self {
+            message,
+            location,
+            lint,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn location(&self) -> Location {
+        self.location
+    }
+
+    pub fn lint(&self) -> Lint {
+        self.lint
+    }
+}
@@ -1,5 +1,7 @@
 use nano_risc_arch::RegisterKind;
 
+use super::ExpressionToken;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ArgumentToken {
     Register { register: RegisterKind },
@@ -9,4 +11,5 @@ pub enum ArgumentToken {
     Label { name: String },
     Constant { name: String },
     Pin { id: usize },
+    Expression { expression: ExpressionToken },
 }
@@ -0,0 +1,55 @@
+use std::fmt::Display;
+
+/// Operators of constant expressions from the lowest precedence to the
+/// highest one, operators in the same group share their precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    Or,
+    Xor,
+    And,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl BinaryOperator {
+    /// Returns `None` when the result overflows or `right` is an invalid
+    /// divisor or shift amount.
+    pub fn apply(self, left: i32, right: i32) -> Option<i32> {
+        match self {
+            BinaryOperator::Or => Some(left | right),
+            BinaryOperator::Xor => Some(left ^ right),
+            BinaryOperator::And => Some(left & right),
+            BinaryOperator::ShiftLeft => left.checked_shl(u32::try_from(right).ok()?),
+            BinaryOperator::ShiftRight => left.checked_shr(u32::try_from(right).ok()?),
+            BinaryOperator::Add => left.checked_add(right),
+            BinaryOperator::Subtract => left.checked_sub(right),
+            BinaryOperator::Multiply => left.checked_mul(right),
+            BinaryOperator::Divide => left.checked_div(right),
+            BinaryOperator::Remainder => left.checked_rem(right),
+        }
+    }
+}
+
+impl Display for BinaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            BinaryOperator::Or => "|",
+            BinaryOperator::Xor => "^",
+            BinaryOperator::And => "&",
+            BinaryOperator::ShiftLeft => "<<",
+            BinaryOperator::ShiftRight => ">>",
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Remainder => "%",
+        };
+
+        write!(f, "{symbol}")
+    }
+}
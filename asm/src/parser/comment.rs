@@ -1,33 +1,134 @@
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::{line_ending, not_line_ending},
-    combinator::eof,
-    sequence::delimited,
+    bytes::complete::{tag, take_until},
+    character::complete::{line_ending, not_line_ending, space0},
+    combinator::{eof, map},
+    multi::many0,
+    sequence::{preceded, terminated},
     IResult,
 };
 
-use super::{ParsingError, Span, Token, TokenKind};
+use super::{ParsingError, ParsingErrorKind, Span, Token, TokenKind};
 
+/// Parses a `# comment`, a `/* block comment */` that can span several lines
+/// or a `## doc comment` documenting the label after it.
 pub fn parse(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
+    alt((doc_comment, line_comment, block_comment))(data)
+        .map(|(remain, token)| (remain, vec![token]))
+}
+
+/// Ends a line after an instruction or a directive, returning the comments
+/// trailing it.
+pub(super) fn end_of_line(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
+    preceded(
+        space0,
+        terminated(
+            many0(terminated(alt((line_comment, block_comment)), space0)),
+            alt((line_ending, eof)),
+        ),
+    )(data)
+}
+
+/// Merges consecutive doc comments and keeps them only before a label, other
+/// doc comments are turned into regular comments.
+pub(super) fn attach_docs(tokens: Vec<Token>) -> Vec<Token> {
+    let mut output: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some(mut token) = tokens.next() {
+        let TokenKind::DocComment { text } = &mut token.kind else {
+            output.push(token);
+            continue;
+        };
+
+        while let Some(next) =
+            tokens.next_if(|token| matches!(token.kind, TokenKind::DocComment { .. }))
+        {
+            if let TokenKind::DocComment { text: line } = next.kind {
+                text.push('\n');
+                text.push_str(&line);
+            }
+        }
+
+        if !matches!(
+            tokens.peek().map(|token| &token.kind),
+            Some(TokenKind::Label { .. })
+        ) {
+            token.kind = TokenKind::Comment {
+                text: std::mem::take(text),
+            };
+        }
+
+        output.push(token);
+    }
+
+    output
+}
+
+fn doc_comment(data: Span) -> IResult<Span, Token, ParsingError> {
+    let (input, _) = tag("##")(data.clone())?;
     let location = data.extra.find_location(data.location_offset()).unwrap();
 
-    delimited(tag("# "), not_line_ending, alt((eof, line_ending)))(data).map(|(remain, text)| {
+    map(not_line_ending, text)(input).map(|(remain, text)| {
         (
             remain,
-            vec![Token {
+            Token {
                 location,
-                kind: TokenKind::Comment {
-                    text: String::from_utf8(text.to_vec()).unwrap(),
-                },
-            }],
+                kind: TokenKind::DocComment { text },
+            },
         )
     })
 }
 
+fn line_comment(data: Span) -> IResult<Span, Token, ParsingError> {
+    let (input, _) = tag("#")(data.clone())?;
+    let location = data.extra.find_location(data.location_offset()).unwrap();
+
+    map(not_line_ending, text)(input).map(|(remain, text)| {
+        (
+            remain,
+            Token {
+                location,
+                kind: TokenKind::Comment { text },
+            },
+        )
+    })
+}
+
+fn block_comment(data: Span) -> IResult<Span, Token, ParsingError> {
+    let (input, _) = tag("/*")(data.clone())?;
+    let location = data.extra.find_location(data.location_offset()).unwrap();
+    let (remain, body) =
+        terminated(take_until("*/"), tag("*/"))(input).map_err(|_: nom::Err<ParsingError>| {
+            nom::Err::Failure(ParsingError::new(
+                String::from("Unterminated block comment"),
+                location,
+                ParsingErrorKind::InvalidComment,
+            ))
+        })?;
+
+    Ok((
+        remain,
+        Token {
+            location,
+            kind: TokenKind::Comment {
+                text: String::from_utf8_lossy(body.fragment()).trim().to_string(),
+            },
+        },
+    ))
+}
+
+/// Drops the space after `#` or `##`, the rest is kept so doc comments can
+/// indent examples.
+fn text(data: Span) -> String {
+    let text = String::from_utf8_lossy(data.fragment());
+
+    text.strip_prefix(' ').unwrap_or(&text).to_string()
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::parser::{Span, Token, TokenKind};
+    use crate::parser::{self, Span, Token, TokenKind};
     use nano_risc_arch::{Location, SourceUnit};
     use pretty_assertions::assert_eq;
 
@@ -42,7 +143,8 @@ mod tests {
                 location: Location {
                     line: 1,
                     column: 1,
-                    offset: 0
+                    offset: 0,
+                    ..Location::default()
                 },
                 kind: TokenKind::Comment {
                     text: String::from("Test comment")
@@ -50,4 +152,46 @@ mod tests {
             }])
         )
     }
+
+    #[test]
+    fn comment_styles() {
+        let kinds = |source: &str| {
+            parser::parse(&SourceUnit::new_anonymous(source.as_bytes().to_vec())).map(|tokens| {
+                tokens
+                    .into_iter()
+                    .map(|token| token.kind)
+                    .collect::<Vec<_>>()
+            })
+        };
+        let comment = |text: &str| TokenKind::Comment {
+            text: String::from(text),
+        };
+
+        assert_eq!(kinds("#todo"), Ok(vec![comment("todo")]));
+        assert_eq!(
+            kinds("/* spans\n   lines */ halt"),
+            Ok(vec![
+                comment("spans\n   lines"),
+                TokenKind::Operation {
+                    operation: String::from("halt")
+                }
+            ])
+        );
+        assert_eq!(
+            kinds("## Adds\n##   two\nadd:\n## dangling"),
+            Ok(vec![
+                TokenKind::DocComment {
+                    text: String::from("Adds\n  two")
+                },
+                TokenKind::Label {
+                    name: String::from("add")
+                },
+                comment("dangling"),
+            ])
+        );
+        assert_eq!(
+            kinds("/* open\nhalt").map_err(|errors| errors[0].message().to_string()),
+            Err(String::from("Unterminated block comment"))
+        );
+    }
 }
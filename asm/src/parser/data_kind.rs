@@ -0,0 +1,14 @@
+/// Kinds of values emitted into the text section by data directives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataKind {
+    /// `.byte 1 2 3`, every value takes 1 byte.
+    Byte,
+    /// `.word 1 2 3`, every value is a little-endian `i32`.
+    Word,
+    /// `.float 1.5 2`, every value is a little-endian `f32`.
+    Float,
+    /// `.space 16`, reserves zero-filled bytes.
+    Space,
+    /// `.string "text"`, a null-terminated string.
+    String,
+}
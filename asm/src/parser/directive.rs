@@ -0,0 +1,251 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till},
+    character::complete::space1,
+    combinator::value,
+    multi::{many0, many_till},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+
+use super::{
+    comment, identifier, instruction, DataKind, ParsingError, ParsingErrorKind, Span, Token,
+    TokenKind,
+};
+
+/// Parses assembler directives, i.e. lines starting with `.`.
+pub fn parse(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
+    alt((macro_start, macro_end, include, constant, data_values))(data)
+}
+
+/// `.macro name param1 param2 ...`
+fn macro_start(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
+    let location = data.extra.find_location(data.location_offset()).unwrap();
+
+    tuple((
+        preceded(terminated(tag(".macro"), space1), identifier),
+        many0(preceded(space1, identifier)),
+        comment::end_of_line,
+    ))(data)
+    .map(|(remain, (name, params, mut comments))| {
+        let mut tokens = vec![Token {
+            location,
+            kind: TokenKind::Macro {
+                name: name.to_lowercase(),
+                params,
+            },
+        }];
+
+        tokens.append(&mut comments);
+
+        (remain, tokens)
+    })
+    .map_err(|err: nom::Err<ParsingError>| {
+        ParsingError::from_nom_error(
+            String::from("Expected a macro name followed by its parameters"),
+            err,
+            ParsingErrorKind::InvalidDirective,
+        )
+    })
+}
+
+/// `.endm`
+fn macro_end(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
+    let location = data.extra.find_location(data.location_offset()).unwrap();
+
+    preceded(tag(".endm"), comment::end_of_line)(data)
+        .map(|(remain, mut comments)| {
+            let mut tokens = vec![Token {
+                location,
+                kind: TokenKind::EndMacro,
+            }];
+
+            tokens.append(&mut comments);
+
+            (remain, tokens)
+        })
+        .map_err(|err: nom::Err<ParsingError>| {
+            ParsingError::from_nom_error(
+                String::from("Expected .endm"),
+                err,
+                ParsingErrorKind::InvalidDirective,
+            )
+        })
+}
+
+/// `.include "path"`
+fn include(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
+    let location = data.extra.find_location(data.location_offset()).unwrap();
+
+    pair(
+        preceded(
+            terminated(tag(".include"), space1),
+            delimited(tag("\""), take_till(|c| c == b'"'), tag("\"")),
+        ),
+        comment::end_of_line,
+    )(data)
+    .map(|(remain, (path, mut comments))| {
+        let mut tokens = vec![Token {
+            location,
+            kind: TokenKind::Include {
+                path: String::from_utf8_lossy(path.fragment()).to_string(),
+            },
+        }];
+
+        tokens.append(&mut comments);
+
+        (remain, tokens)
+    })
+    .map_err(|err: nom::Err<ParsingError>| {
+        ParsingError::from_nom_error(
+            String::from("Expected a quoted file path"),
+            err,
+            ParsingErrorKind::InvalidDirective,
+        )
+    })
+}
+
+/// `.equ NAME value`
+fn constant(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
+    let location = data.extra.find_location(data.location_offset()).unwrap();
+
+    pair(
+        preceded(
+            terminated(tag(".equ"), space1),
+            terminated(identifier, space1),
+        ),
+        many_till(instruction::arg_parser, comment::end_of_line),
+    )(data)
+    .map(|(remain, (name, (mut value, mut comments)))| {
+        let mut tokens = vec![Token {
+            location,
+            kind: TokenKind::Constant { name },
+        }];
+
+        tokens.append(&mut value);
+        tokens.append(&mut comments);
+
+        (remain, tokens)
+    })
+    .map_err(|err: nom::Err<ParsingError>| {
+        ParsingError::from_nom_error(
+            String::from("Expected a constant name followed by its value"),
+            err,
+            ParsingErrorKind::InvalidDirective,
+        )
+    })
+}
+
+/// `.byte`, `.word`, `.float`, `.space` or `.string` followed by values.
+fn data_values(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
+    let location = data.extra.find_location(data.location_offset()).unwrap();
+
+    pair(
+        terminated(
+            alt((
+                value(DataKind::Byte, tag(".byte")),
+                value(DataKind::Word, tag(".word")),
+                value(DataKind::Float, tag(".float")),
+                value(DataKind::Space, tag(".space")),
+                value(DataKind::String, tag(".string")),
+            )),
+            space1,
+        ),
+        many_till(instruction::arg_parser, comment::end_of_line),
+    )(data)
+    .map(|(remain, (kind, (mut values, mut comments)))| {
+        let mut tokens = vec![Token {
+            location,
+            kind: TokenKind::Data { kind },
+        }];
+
+        tokens.append(&mut values);
+        tokens.append(&mut comments);
+
+        (remain, tokens)
+    })
+    .map_err(|err: nom::Err<ParsingError>| {
+        ParsingError::from_nom_error(
+            String::from("Expected data values"),
+            err,
+            ParsingErrorKind::InvalidDirective,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{ArgumentToken, Span, Token, TokenKind};
+    use nano_risc_arch::{Location, SourceUnit};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_macro() {
+        let unit =
+            SourceUnit::new_anonymous(".macro save_call target reg\n.endm".as_bytes().to_vec());
+        let (remain, start) = super::parse(Span::new_extra(unit.data(), unit.clone())).unwrap();
+        let end = super::parse(remain).map(|(_, token)| token);
+
+        assert_eq!(
+            start,
+            vec![Token {
+                location: Location {
+                    line: 1,
+                    column: 1,
+                    offset: 0,
+                    ..Location::default()
+                },
+                kind: TokenKind::Macro {
+                    name: String::from("save_call"),
+                    params: vec![String::from("target"), String::from("reg")]
+                }
+            }]
+        );
+        assert_eq!(
+            end,
+            Ok(vec![Token {
+                location: Location {
+                    line: 2,
+                    column: 1,
+                    offset: 28,
+                    ..Location::default()
+                },
+                kind: TokenKind::EndMacro
+            }])
+        );
+    }
+
+    #[test]
+    fn parse_constant() {
+        let unit = SourceUnit::new_anonymous(".equ MAX_HP 100\n".as_bytes().to_vec());
+        let tokens = super::parse(Span::new_extra(unit.data(), unit.clone())).map(|(_, t)| t);
+
+        assert_eq!(
+            tokens,
+            Ok(vec![
+                Token {
+                    location: Location {
+                        line: 1,
+                        column: 1,
+                        offset: 0,
+                        ..Location::default()
+                    },
+                    kind: TokenKind::Constant {
+                        name: String::from("MAX_HP")
+                    }
+                },
+                Token {
+                    location: Location {
+                        line: 1,
+                        column: 13,
+                        offset: 12,
+                        ..Location::default()
+                    },
+                    kind: TokenKind::Argument {
+                        argument: ArgumentToken::Int { value: 100 }
+                    }
+                }
+            ])
+        );
+    }
+}
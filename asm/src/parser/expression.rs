@@ -0,0 +1,215 @@
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{space0, space1},
+    combinator::{consumed, map, map_opt, value, verify},
+    multi::many0,
+    sequence::{delimited, pair, preceded, terminated},
+    IResult,
+};
+
+use super::{
+    instruction, BinaryOperator, ExpressionToken, ParsingError, Span, Token, TokenKind,
+    UnaryOperator,
+};
+
+/// Parses a constant expression, e.g. `.ram_end - 256` or `(label + 1) * 4`.
+///
+/// Binary operators are either surrounded by spaces or written without them,
+/// so `4 -1` remains two arguments. Single literals, labels and constants
+/// are left to their own argument parsers.
+pub fn parse(data: Span) -> IResult<Span, ExpressionToken, ParsingError> {
+    map(
+        verify(
+            consumed(or),
+            |(text, expression): &(Span, ExpressionToken)| {
+                text.starts_with(b"(")
+                    || matches!(
+                        expression,
+                        ExpressionToken::Unary { .. } | ExpressionToken::Binary { .. }
+                    )
+            },
+        ),
+        |(_, expression)| expression,
+    )(data)
+}
+
+fn or(data: Span) -> IResult<Span, ExpressionToken, ParsingError> {
+    chain(data, xor, |data| value(BinaryOperator::Or, tag("|"))(data))
+}
+
+fn xor(data: Span) -> IResult<Span, ExpressionToken, ParsingError> {
+    chain(data, and, |data| value(BinaryOperator::Xor, tag("^"))(data))
+}
+
+fn and(data: Span) -> IResult<Span, ExpressionToken, ParsingError> {
+    chain(data, shift, |data| {
+        value(BinaryOperator::And, tag("&"))(data)
+    })
+}
+
+fn shift(data: Span) -> IResult<Span, ExpressionToken, ParsingError> {
+    chain(data, sum, |data| {
+        alt((
+            value(BinaryOperator::ShiftLeft, tag("<<")),
+            value(BinaryOperator::ShiftRight, tag(">>")),
+        ))(data)
+    })
+}
+
+fn sum(data: Span) -> IResult<Span, ExpressionToken, ParsingError> {
+    chain(data, product, |data| {
+        alt((
+            value(BinaryOperator::Add, tag("+")),
+            value(BinaryOperator::Subtract, tag("-")),
+        ))(data)
+    })
+}
+
+fn product(data: Span) -> IResult<Span, ExpressionToken, ParsingError> {
+    chain(data, unary, |data| {
+        alt((
+            value(BinaryOperator::Multiply, tag("*")),
+            value(BinaryOperator::Divide, tag("/")),
+            value(BinaryOperator::Remainder, tag("%")),
+        ))(data)
+    })
+}
+
+/// Parses `operand (operator operand)*` into a left-associative tree.
+fn chain(
+    data: Span,
+    operand: fn(Span) -> IResult<Span, ExpressionToken, ParsingError>,
+    operator: fn(Span) -> IResult<Span, BinaryOperator, ParsingError>,
+) -> IResult<Span, ExpressionToken, ParsingError> {
+    let spaced_operator = alt((delimited(space1, operator, space1), operator));
+
+    pair(operand, many0(pair(spaced_operator, operand)))(data).map(|(remain, (first, rest))| {
+        let expression =
+            rest.into_iter()
+                .fold(first, |left, (operator, right)| ExpressionToken::Binary {
+                    operator,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                });
+
+        (remain, expression)
+    })
+}
+
+fn unary(data: Span) -> IResult<Span, ExpressionToken, ParsingError> {
+    alt((
+        primary,
+        map(
+            pair(
+                alt((
+                    value(UnaryOperator::Negate, tag("-")),
+                    value(UnaryOperator::Not, tag("~")),
+                )),
+                unary,
+            ),
+            |(operator, operand)| ExpressionToken::Unary {
+                operator,
+                operand: Box::new(operand),
+            },
+        ),
+    ))(data)
+}
+
+fn primary(data: Span) -> IResult<Span, ExpressionToken, ParsingError> {
+    alt((
+        map_opt(
+            alt((
+                instruction::bin_int_arg,
+                instruction::hex_int_arg,
+                instruction::int_arg,
+                instruction::char_arg,
+                instruction::constant_arg,
+                instruction::label_arg,
+            )),
+            |token: Token| match token.kind {
+                TokenKind::Argument { argument } => ExpressionToken::from_argument(&argument),
+                _ => None,
+            },
+        ),
+        delimited(terminated(tag("("), space0), or, preceded(space0, tag(")"))),
+    ))(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{BinaryOperator, ExpressionToken, Span, UnaryOperator};
+    use nano_risc_arch::SourceUnit;
+    use pretty_assertions::assert_eq;
+
+    fn parse(source: &str) -> Option<ExpressionToken> {
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+
+        super::parse(Span::new_extra(unit.data(), unit.clone()))
+            .ok()
+            .map(|(_, expression)| expression)
+    }
+
+    fn int(value: i32) -> Box<ExpressionToken> {
+        Box::new(ExpressionToken::Int { value })
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(
+            parse("1 + 2 * 3"),
+            Some(ExpressionToken::Binary {
+                operator: BinaryOperator::Add,
+                left: int(1),
+                right: Box::new(ExpressionToken::Binary {
+                    operator: BinaryOperator::Multiply,
+                    left: int(2),
+                    right: int(3)
+                })
+            })
+        );
+        assert_eq!(
+            parse("(.data+1)<<2"),
+            Some(ExpressionToken::Binary {
+                operator: BinaryOperator::ShiftLeft,
+                left: Box::new(ExpressionToken::Binary {
+                    operator: BinaryOperator::Add,
+                    left: Box::new(ExpressionToken::Constant {
+                        name: String::from("data")
+                    }),
+                    right: int(1)
+                }),
+                right: int(2)
+            })
+        );
+        assert_eq!(
+            parse("-start - 1"),
+            Some(ExpressionToken::Binary {
+                operator: BinaryOperator::Subtract,
+                left: Box::new(ExpressionToken::Unary {
+                    operator: UnaryOperator::Negate,
+                    operand: Box::new(ExpressionToken::Label {
+                        name: String::from("start")
+                    })
+                }),
+                right: int(1)
+            })
+        );
+    }
+
+    #[test]
+    fn single_values() {
+        assert_eq!(parse("5"), None);
+        assert_eq!(parse("start"), None);
+        assert_eq!(parse("4 -1"), None);
+        assert_eq!(parse("(5)"), Some(ExpressionToken::Int { value: 5 }));
+        assert_eq!(
+            parse("( 1 ) + 2"),
+            Some(ExpressionToken::Binary {
+                operator: BinaryOperator::Add,
+                left: int(1),
+                right: int(2)
+            })
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use super::{ArgumentToken, BinaryOperator, UnaryOperator};
+
+/// Integer expression evaluated at compile time, e.g. `.data + 4 * 8`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionToken {
+    Int {
+        value: i32,
+    },
+    Label {
+        name: String,
+    },
+    Constant {
+        name: String,
+    },
+    Unary {
+        operator: UnaryOperator,
+        operand: Box<ExpressionToken>,
+    },
+    Binary {
+        operator: BinaryOperator,
+        left: Box<ExpressionToken>,
+        right: Box<ExpressionToken>,
+    },
+}
+
+impl ExpressionToken {
+    /// Converts arguments that can be used as operands.
+    pub fn from_argument(argument: &ArgumentToken) -> Option<Self> {
+        match argument {
+            ArgumentToken::Int { value } => Some(Self::Int { value: *value }),
+            ArgumentToken::Label { name } => Some(Self::Label { name: name.clone() }),
+            ArgumentToken::Constant { name } => Some(Self::Constant { name: name.clone() }),
+            ArgumentToken::Expression { expression } => Some(expression.clone()),
+            _ => None,
+        }
+    }
+
+    /// Replaces every label with the result of `replace`.
+    pub fn map_labels(&self, replace: &impl Fn(&String) -> Self) -> Self {
+        match self {
+            Self::Label { name } => replace(name),
+            Self::Unary { operator, operand } => Self::Unary {
+                operator: *operator,
+                operand: Box::new(operand.map_labels(replace)),
+            },
+            Self::Binary {
+                operator,
+                left,
+                right,
+            } => Self::Binary {
+                operator: *operator,
+                left: Box::new(left.map_labels(replace)),
+                right: Box::new(right.map_labels(replace)),
+            },
+            _ => self.clone(),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use nano_risc_arch::SourceUnit;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// Loads files pulled in with `.include`.
+pub trait FileResolver {
+    /// Loads `path` included from `from`.
+    ///
+    /// The returned unit's name identifies the file, so the same file must
+    /// always get the same name for include cycles to be detected.
+    fn resolve(&self, from: &SourceUnit, path: &str) -> Result<SourceUnit, String>;
+}
+
+/// Reads files from the disk relative to the including file.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FsResolver;
+
+impl FileResolver for FsResolver {
+    fn resolve(&self, from: &SourceUnit, path: &str) -> Result<SourceUnit, String> {
+        let path = normalize(
+            &Path::new(from.name())
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(path),
+        );
+        let data =
+            fs::read(&path).map_err(|err| format!("Can't read {}: {err}", path.display()))?;
+
+        Ok(SourceUnit::new(path.display().to_string(), data))
+    }
+}
+
+/// Serves files from memory, paths are relative to the including file.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryResolver {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_file(&mut self, path: &str, data: Vec<u8>) {
+        self.files
+            .insert(normalize(Path::new(path)).display().to_string(), data);
+    }
+}
+
+impl FileResolver for MemoryResolver {
+    fn resolve(&self, from: &SourceUnit, path: &str) -> Result<SourceUnit, String> {
+        let path = normalize(
+            &Path::new(from.name())
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(path),
+        )
+        .display()
+        .to_string();
+        let Some(data) = self.files.get(&path) else {
+            return Err(format!("File not found: {path}"));
+        };
+
+        Ok(SourceUnit::new(path, data.clone()))
+    }
+}
+
+/// Removes `.` and resolves `..` without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+
+    normalized
+}
@@ -1,20 +1,22 @@
 use std::str::FromStr;
 
+use nano_risc_arch::Location;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_till},
+    bytes::complete::tag,
     character::{
         self,
-        complete::{alpha1, char, hex_digit1, line_ending, one_of, space0, space1},
+        complete::{alpha1, alphanumeric1, char, digit1, hex_digit1, one_of, space0, space1},
     },
-    combinator::{eof, opt, recognize},
-    multi::{many0, many1, many_till},
-    sequence::{delimited, pair, preceded, separated_pair, terminated},
+    combinator::{not, opt, recognize},
+    multi::{many0, many0_count, many1, many_till},
+    sequence::{pair, preceded, terminated, tuple},
     IResult,
 };
 
 use super::{
-    identifier, register, ArgumentToken, ParsingError, ParsingErrorKind, Span, Token, TokenKind,
+    comment, expression, identifier, label_name, literal, register, ArgumentToken, ParsingError,
+    ParsingErrorKind, Span, Token, TokenKind,
 };
 
 pub fn parse(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
@@ -24,31 +26,35 @@ pub fn parse(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
 fn parse_single(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
     let location = data.extra.find_location(data.location_offset()).unwrap();
 
-    self::operation_parser(data).map(|(remain, name)| {
-        (
-            remain,
-            vec![Token {
+    pair(self::operation_parser, comment::end_of_line)(data).map(
+        |(remain, (name, mut comments))| {
+            let mut tokens = vec![Token {
                 location,
                 kind: TokenKind::Operation { operation: name },
-            }],
-        )
-    })
+            }];
+
+            tokens.append(&mut comments);
+
+            (remain, tokens)
+        },
+    )
 }
 
 fn parse_with_args(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
     let location = data.extra.find_location(data.location_offset()).unwrap();
 
     pair(
-        self::operation_parser,
-        many_till(self::arg_parser, alt((line_ending, eof))),
+        terminated(self::operation_parser, space1),
+        many_till(self::arg_parser, comment::end_of_line),
     )(data)
-    .map(|(remain, (name, (mut args, _)))| {
+    .map(|(remain, (name, (mut args, mut comments)))| {
         let mut tokens = vec![Token {
             location,
             kind: TokenKind::Operation { operation: name },
         }];
 
         tokens.append(&mut args);
+        tokens.append(&mut comments);
 
         (remain, tokens)
     })
@@ -70,123 +76,169 @@ fn pin_arg(data: Span) -> IResult<Span, Token, ParsingError> {
     })
 }
 
-fn int_arg(data: Span) -> IResult<Span, Token, ParsingError> {
+pub(super) fn int_arg(data: Span) -> IResult<Span, Token, ParsingError> {
     let location = data.extra.find_location(data.location_offset()).unwrap();
+    let (remain, text) = recognize(pair(opt(one_of("+-")), digit1))(data)?;
+    let text = String::from_utf8(text.to_vec()).unwrap();
+    let Ok(value) = text.parse::<i32>() else {
+        return Err(overflow(location, &text));
+    };
 
-    pair(opt(alt((tag("+"), tag("-")))), character::complete::i32)(data).map(
-        |(remain, (sign, value))| {
-            let sign = sign
-                .map(|value| String::from_utf8(value.to_vec()).unwrap())
-                .unwrap_or_else(|| String::from("+"));
+    Ok((remain, int_token(location, value)))
+}
 
-            (
-                remain,
-                Token {
-                    location,
-                    kind: TokenKind::Argument {
-                        argument: ArgumentToken::Int {
-                            value: str::parse(&format!("{sign}{value}")).unwrap(),
-                        },
-                    },
-                },
-            )
+pub(super) fn hex_int_arg(data: Span) -> IResult<Span, Token, ParsingError> {
+    let location = data.extra.find_location(data.location_offset()).unwrap();
+
+    pair(opt(one_of("+-")), preceded(tag("0x"), hex_digit1))(data).and_then(
+        |(remain, (sign, digits))| {
+            let digits = String::from_utf8(digits.to_vec()).unwrap();
+            let value = bits(sign, u32::from_str_radix(&digits, 16).ok())
+                .ok_or_else(|| overflow(location, &format!("0x{digits}")))?;
+
+            Ok((remain, int_token(location, value)))
         },
     )
 }
 
-fn hex_int_arg(data: Span) -> IResult<Span, Token, ParsingError> {
+pub(super) fn bin_int_arg(data: Span) -> IResult<Span, Token, ParsingError> {
     let location = data.extra.find_location(data.location_offset()).unwrap();
 
-    pair(
-        opt(alt((tag("+"), tag("-")))),
-        preceded(tag("0x"), hex_digit1),
+    preceded(
+        tag("0b"),
+        recognize(many1(terminated(one_of("01"), many0(char('_'))))),
     )(data)
-    .map(|(remain, (sign, value))| {
-        let sign = sign
-            .map(|value| String::from_utf8(value.to_vec()).unwrap())
-            .unwrap_or_else(|| String::from("+"));
-        let value = String::from_utf8(value.to_vec()).unwrap();
+    .and_then(|(remain, digits)| {
+        let digits: String = String::from_utf8(digits.to_vec())
+            .unwrap()
+            .chars()
+            .filter(|digit| *digit != '_')
+            .collect();
+        let value = bits(None, u32::from_str_radix(&digits, 2).ok())
+            .ok_or_else(|| overflow(location, &format!("0b{digits}")))?;
+
+        Ok((remain, int_token(location, value)))
+    })
+}
 
-        (
+/// Hex and binary literals may use all 32 bits, e.g. `0xffffffff` is `-1`.
+fn bits(sign: Option<char>, magnitude: Option<u32>) -> Option<i32> {
+    match sign {
+        Some('-') => i32::try_from(-i64::from(magnitude?)).ok(),
+        _ => Some(magnitude? as i32),
+    }
+}
+
+fn int_token(location: Location, value: i32) -> Token {
+    Token {
+        location,
+        kind: TokenKind::Argument {
+            argument: ArgumentToken::Int { value },
+        },
+    }
+}
+
+fn overflow(location: Location, text: &str) -> nom::Err<ParsingError> {
+    nom::Err::Failure(ParsingError::new(
+        format!("Integer literal {text} doesn't fit in 32 bits"),
+        location,
+        ParsingErrorKind::IntegerOverflow,
+    ))
+}
+
+/// Parses `1.5`, `.5`, `1e3` or `2.5e-3`.
+fn float_arg(data: Span) -> IResult<Span, Token, ParsingError> {
+    let location = data.extra.find_location(data.location_offset()).unwrap();
+    let exponent = || recognize(tuple((one_of("eE"), opt(one_of("+-")), digit1)));
+
+    recognize(pair(
+        opt(one_of("+-")),
+        alt((
+            recognize(tuple((digit1, tag("."), digit1, opt(exponent())))),
+            recognize(tuple((tag("."), digit1, opt(exponent())))),
+            recognize(pair(digit1, exponent())),
+        )),
+    ))(data)
+    .and_then(|(remain, text)| {
+        let text = String::from_utf8(text.to_vec()).unwrap();
+        let value = f32::from_str(&text).unwrap();
+
+        if !value.is_finite() {
+            return Err(nom::Err::Failure(ParsingError::new(
+                format!("Float literal {text} is out of range"),
+                location,
+                ParsingErrorKind::InvalidArgument,
+            )));
+        }
+
+        Ok((
             remain,
             Token {
                 location,
                 kind: TokenKind::Argument {
-                    argument: ArgumentToken::Int {
-                        value: i32::from_str_radix(&format!("{sign}{value}"), 16).unwrap(),
-                    },
+                    argument: ArgumentToken::Float { value },
                 },
             },
-        )
+        ))
     })
 }
 
-fn bin_int_arg(data: Span) -> IResult<Span, Token, ParsingError> {
+fn string_arg(data: Span) -> IResult<Span, Token, ParsingError> {
     let location = data.extra.find_location(data.location_offset()).unwrap();
 
-    preceded(
-        tag("0b"),
-        recognize(many1(terminated(one_of("01"), many0(char('_'))))),
-    )(data)
-    .map(|(remain, value)| {
-        let value = String::from_utf8(value.to_vec()).unwrap();
-
+    literal::string(data).map(|(remain, value)| {
         (
             remain,
             Token {
                 location,
                 kind: TokenKind::Argument {
-                    argument: ArgumentToken::Int {
-                        value: i32::from_str_radix(&value, 2).unwrap(),
-                    },
+                    argument: ArgumentToken::String { value },
                 },
             },
         )
     })
 }
 
-fn float_arg(data: Span) -> IResult<Span, Token, ParsingError> {
+/// Parses a character literal, it stands for the character's code.
+pub(super) fn char_arg(data: Span) -> IResult<Span, Token, ParsingError> {
     let location = data.extra.find_location(data.location_offset()).unwrap();
 
-    pair(
-        opt(alt((tag("+"), tag("-")))),
-        recognize(separated_pair(
-            character::complete::i32,
-            tag("."),
-            character::complete::i32,
-        )),
-    )(data)
-    .map(|(remain, (sign, value))| {
-        let sign = sign
-            .map(|value| String::from_utf8(value.to_vec()).unwrap())
-            .unwrap_or_else(|| String::from("+"));
-        let value = String::from_utf8(value.to_vec()).unwrap();
+    literal::character(data).map(|(remain, value)| (remain, int_token(location, value as i32)))
+}
 
+pub(super) fn label_arg(data: Span) -> IResult<Span, Token, ParsingError> {
+    let location = data.extra.find_location(data.location_offset()).unwrap();
+
+    label_name(data).map(|(remain, name)| {
         (
             remain,
             Token {
                 location,
                 kind: TokenKind::Argument {
-                    argument: ArgumentToken::Float {
-                        value: f32::from_str(&format!("{sign}{value}")).unwrap(),
-                    },
+                    argument: ArgumentToken::Label { name },
                 },
             },
         )
     })
 }
 
-fn string_arg(data: Span) -> IResult<Span, Token, ParsingError> {
+/// Parses a reference to the next (`1f`) or previous (`1b`) anonymous
+/// label.
+fn anonymous_label_arg(data: Span) -> IResult<Span, Token, ParsingError> {
     let location = data.extra.find_location(data.location_offset()).unwrap();
 
-    delimited(tag("\""), take_till(|c| c == b'"'), tag("\""))(data).map(|(remain, value)| {
+    terminated(
+        recognize(pair(digit1, one_of("fb"))),
+        not(alt((alphanumeric1, tag("_")))),
+    )(data)
+    .map(|(remain, name)| {
         (
             remain,
             Token {
                 location,
                 kind: TokenKind::Argument {
-                    argument: ArgumentToken::String {
-                        value: String::from_utf8(value.to_vec()).unwrap(),
+                    argument: ArgumentToken::Label {
+                        name: String::from_utf8(name.fragment().to_vec()).unwrap(),
                     },
                 },
             },
@@ -194,74 +246,76 @@ fn string_arg(data: Span) -> IResult<Span, Token, ParsingError> {
     })
 }
 
-fn label_arg(data: Span) -> IResult<Span, Token, ParsingError> {
+fn register_arg(data: Span) -> IResult<Span, Token, ParsingError> {
     let location = data.extra.find_location(data.location_offset()).unwrap();
 
-    identifier(data).map(|(remain, name)| {
+    register::parse(data).map(|(remain, kind)| {
         (
             remain,
             Token {
                 location,
                 kind: TokenKind::Argument {
-                    argument: ArgumentToken::Label { name },
+                    argument: ArgumentToken::Register { register: kind },
                 },
             },
         )
     })
 }
 
-fn register_arg(data: Span) -> IResult<Span, Token, ParsingError> {
+pub(super) fn constant_arg(data: Span) -> IResult<Span, Token, ParsingError> {
     let location = data.extra.find_location(data.location_offset()).unwrap();
 
-    register::parse(data).map(|(remain, kind)| {
+    preceded(tag("."), identifier)(data).map(|(remain, name)| {
         (
             remain,
             Token {
                 location,
                 kind: TokenKind::Argument {
-                    argument: ArgumentToken::Register { register: kind },
+                    argument: ArgumentToken::Constant { name },
                 },
             },
         )
     })
 }
 
-fn constant_arg(data: Span) -> IResult<Span, Token, ParsingError> {
+fn expression_arg(data: Span) -> IResult<Span, Token, ParsingError> {
     let location = data.extra.find_location(data.location_offset()).unwrap();
 
-    preceded(tag("."), identifier)(data).map(|(remain, name)| {
+    expression::parse(data).map(|(remain, expression)| {
         (
             remain,
             Token {
                 location,
                 kind: TokenKind::Argument {
-                    argument: ArgumentToken::Constant { name },
+                    argument: ArgumentToken::Expression { expression },
                 },
             },
         )
     })
 }
 
-fn arg_parser(data: Span) -> IResult<Span, Token, ParsingError> {
+pub(crate) fn arg_parser(data: Span) -> IResult<Span, Token, ParsingError> {
     terminated(
         alt((
             self::register_arg,
             self::pin_arg,
+            self::expression_arg,
             self::bin_int_arg,
             self::hex_int_arg,
+            self::anonymous_label_arg,
             self::float_arg,
             self::int_arg,
+            self::char_arg,
             self::string_arg,
             self::constant_arg,
             self::label_arg,
         )),
         space0,
     )(data)
-    .map(|(remain, arg)| (remain, arg))
     .map_err(|err| {
         ParsingError::from_nom_error(
             String::from(
-                "Expected a register, pin, integer, float, string, constant or label argument",
+                "Expected a register, pin, integer, float, character, string, constant, label or expression argument",
             ),
             err,
             ParsingErrorKind::InvalidArgument,
@@ -270,17 +324,19 @@ fn arg_parser(data: Span) -> IResult<Span, Token, ParsingError> {
 }
 
 fn operation_parser(data: Span) -> IResult<Span, String, ParsingError> {
-    terminated(alpha1, alt((line_ending, eof, space1)))(data).map(|(remain, name)| {
-        (
-            remain,
-            String::from_utf8(name.to_vec()).unwrap().to_lowercase(),
-        )
-    })
+    recognize(pair(alpha1, many0_count(alt((alphanumeric1, tag("_"))))))(data).map(
+        |(remain, name)| {
+            (
+                remain,
+                String::from_utf8(name.to_vec()).unwrap().to_lowercase(),
+            )
+        },
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::parser::{ArgumentToken, Span, Token, TokenKind};
+    use crate::parser::{ArgumentToken, ParsingErrorKind, Span, Token, TokenKind};
     use nano_risc_arch::{Location, RegisterKind, RegisterMode, SourceUnit};
     use pretty_assertions::assert_eq;
 
@@ -295,7 +351,8 @@ mod tests {
                 location: Location {
                     line: 1,
                     column: 1,
-                    offset: 0
+                    offset: 0,
+                    ..Location::default()
                 },
                 kind: TokenKind::Operation {
                     operation: String::from("add"),
@@ -320,7 +377,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 1,
-                        offset: 0
+                        offset: 0,
+                        ..Location::default()
                     },
                     kind: TokenKind::Operation {
                         operation: String::from("add"),
@@ -330,7 +388,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 5,
-                        offset: 4
+                        offset: 4,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Register {
@@ -345,7 +404,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 9,
-                        offset: 8
+                        offset: 8,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Pin { id: 4 }
@@ -355,7 +415,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 12,
-                        offset: 11
+                        offset: 11,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Int { value: 78 }
@@ -365,7 +426,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 15,
-                        offset: 14
+                        offset: 14,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Int { value: -99 }
@@ -375,7 +437,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 19,
-                        offset: 18
+                        offset: 18,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Int { value: 0xFF }
@@ -385,7 +448,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 24,
-                        offset: 23
+                        offset: 23,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Int { value: -0xDD }
@@ -395,7 +459,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 30,
-                        offset: 29
+                        offset: 29,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Int { value: 0b0101 }
@@ -405,7 +470,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 37,
-                        offset: 36
+                        offset: 36,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Float { value: 12.66 }
@@ -415,7 +481,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 43,
-                        offset: 42
+                        offset: 42,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Float { value: -4.12 }
@@ -425,7 +492,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 49,
-                        offset: 48
+                        offset: 48,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::String {
@@ -437,7 +505,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 65,
-                        offset: 64
+                        offset: 64,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Label {
@@ -449,7 +518,8 @@ mod tests {
                     location: Location {
                         line: 1,
                         column: 71,
-                        offset: 70
+                        offset: 70,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Constant {
@@ -460,4 +530,74 @@ mod tests {
             ])
         );
     }
+
+    fn arguments(source: &str) -> Result<Vec<ArgumentToken>, (String, ParsingErrorKind)> {
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+
+        super::parse(Span::new_extra(unit.data(), unit.clone()))
+            .map(|(_, tokens)| {
+                tokens
+                    .into_iter()
+                    .filter_map(|token| match token.kind {
+                        TokenKind::Argument { argument } => Some(argument),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .map_err(|err| match err {
+                nom::Err::Error(err) | nom::Err::Failure(err) => {
+                    (err.message().to_string(), err.kind())
+                }
+                nom::Err::Incomplete(_) => unreachable!(),
+            })
+    }
+
+    #[test]
+    fn parse_literals() {
+        assert_eq!(
+            arguments("dbg 1e3 2.5e-1 .5 -.25E2 'A' '\\n' \"a\\tb\" 0xffffffff 0b1_0"),
+            Ok(vec![
+                ArgumentToken::Float { value: 1000.0 },
+                ArgumentToken::Float { value: 0.25 },
+                ArgumentToken::Float { value: 0.5 },
+                ArgumentToken::Float { value: -25.0 },
+                ArgumentToken::Int { value: 65 },
+                ArgumentToken::Int { value: 10 },
+                ArgumentToken::String {
+                    value: String::from("a\tb")
+                },
+                ArgumentToken::Int { value: -1 },
+                ArgumentToken::Int { value: 2 },
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_overflow() {
+        assert_eq!(
+            arguments("dbg 2147483648"),
+            Err((
+                String::from("Integer literal 2147483648 doesn't fit in 32 bits"),
+                ParsingErrorKind::IntegerOverflow
+            ))
+        );
+        assert_eq!(
+            arguments("dbg -2147483648"),
+            Ok(vec![ArgumentToken::Int { value: i32::MIN }])
+        );
+        assert_eq!(
+            arguments("dbg 0x100000000"),
+            Err((
+                String::from("Integer literal 0x100000000 doesn't fit in 32 bits"),
+                ParsingErrorKind::IntegerOverflow
+            ))
+        );
+        assert_eq!(
+            arguments("dbg 1e39"),
+            Err((
+                String::from("Float literal 1e39 is out of range"),
+                ParsingErrorKind::InvalidArgument
+            ))
+        );
+    }
 }
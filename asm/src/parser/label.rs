@@ -1,11 +1,18 @@
-use nom::{bytes::complete::tag, sequence::terminated, IResult};
+use nom::{
+    branch::alt, bytes::complete::tag, character::complete::digit1, combinator::map,
+    sequence::terminated, IResult,
+};
 
-use super::{identifier, ParsingError, ParsingErrorKind, Span, Token, TokenKind};
+use super::{label_name, ParsingError, ParsingErrorKind, Span, Token, TokenKind};
 
+/// Parses `name:`, a local `@name:` or an anonymous `1:` label.
 pub fn parse(data: Span) -> IResult<Span, Vec<Token>, ParsingError> {
     let location = data.extra.find_location(data.location_offset()).unwrap();
+    let anonymous = map(digit1, |digits: Span| {
+        String::from_utf8(digits.fragment().to_vec()).unwrap()
+    });
 
-    terminated(identifier, tag(":"))(data)
+    terminated(alt((label_name, anonymous)), tag(":"))(data)
         .map(|(remain, name)| {
             (
                 remain,
@@ -41,7 +48,8 @@ mod tests {
                 location: Location {
                     line: 1,
                     column: 1,
-                    offset: 0
+                    offset: 0,
+                    ..Location::default()
                 },
                 kind: TokenKind::Label {
                     name: String::from("test")
@@ -49,4 +57,23 @@ mod tests {
             }])
         )
     }
+
+    #[test]
+    fn parse_local_and_anonymous() {
+        let name = |source: &str| {
+            let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+
+            super::parse(Span::new_extra(unit.data(), unit.clone())).map(|(_, token)| match &token
+                [0]
+            .kind
+            {
+                TokenKind::Label { name } => name.clone(),
+                _ => unreachable!(),
+            })
+        };
+
+        assert_eq!(name("@loop:"), Ok(String::from("@loop")));
+        assert_eq!(name("12:"), Ok(String::from("12")));
+        assert!(name("@:").is_err());
+    }
 }
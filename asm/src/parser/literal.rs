@@ -0,0 +1,286 @@
+use nano_risc_arch::SourceUnit;
+use nom::{bytes::complete::take, IResult, Slice};
+
+use super::{ParsingError, ParsingErrorKind, Span};
+
+/// Parses a double-quoted string, see [`escape`] for the escape sequences.
+pub(super) fn string(data: Span) -> IResult<Span, String, ParsingError> {
+    if !data.starts_with(b"\"") {
+        return Err(nom::Err::Error(error(
+            &data,
+            String::from("Expected a string"),
+        )));
+    }
+
+    let (mut input, _) = take(1usize)(data.clone())?;
+    let mut bytes = Vec::new();
+
+    loop {
+        match input.fragment().first() {
+            None => {
+                return Err(nom::Err::Failure(error(
+                    &data,
+                    String::from("Unterminated string"),
+                )))
+            }
+            Some(b'"') => {
+                let (remain, _) = take(1usize)(input)?;
+
+                input = remain;
+                break;
+            }
+            Some(b'\\') => {
+                let (remain, character) = escape(input)?;
+
+                bytes.extend_from_slice(character.encode_utf8(&mut [0; 4]).as_bytes());
+                input = remain;
+            }
+            Some(byte) => {
+                bytes.push(*byte);
+                input = take(1usize)(input)?.0;
+            }
+        }
+    }
+
+    match String::from_utf8(bytes) {
+        Ok(value) => Ok((input, value)),
+        Err(_) => Err(nom::Err::Failure(error(
+            &data,
+            String::from("String isn't valid UTF-8"),
+        ))),
+    }
+}
+
+/// Maps `offset` in the value of the string literal at `start` in `unit`
+/// back to the source, relative to the opening quote, so escapes before it
+/// count with their written length.
+pub(crate) fn string_offset(unit: &SourceUnit, start: usize, offset: usize) -> usize {
+    let mut input = Span::new_extra(unit.data(), unit.clone()).slice(start + 1..);
+    let mut length = 0;
+
+    while length < offset {
+        let next = match input.fragment().first() {
+            Some(b'\\') => escape(input.clone()).map(|(remain, value)| (remain, value.len_utf8())),
+            Some(b'"') | None => break,
+            Some(_) => Ok((input.slice(1..), 1)),
+        };
+        let Ok((remain, width)) = next else {
+            break;
+        };
+
+        length += width;
+        input = remain;
+    }
+
+    input.location_offset() - start
+}
+
+/// Parses a single-quoted character, e.g. `'a'` or `'\n'`.
+pub(super) fn character(data: Span) -> IResult<Span, char, ParsingError> {
+    if !data.starts_with(b"'") {
+        return Err(nom::Err::Error(error(
+            &data,
+            String::from("Expected a character"),
+        )));
+    }
+
+    let (input, _) = take(1usize)(data.clone())?;
+    let (input, value) = match input.fragment().first() {
+        Some(b'\\') => escape(input)?,
+        Some(first) => {
+            let width = match first {
+                0x00..=0x7f => 1,
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                _ => 4,
+            };
+            let value = input
+                .fragment()
+                .get(..width)
+                .and_then(|bytes| std::str::from_utf8(bytes).ok())
+                .and_then(|text| text.chars().next())
+                .filter(|value| *value != '\'');
+
+            match value {
+                Some(value) => (take(width)(input)?.0, value),
+                None => {
+                    return Err(nom::Err::Failure(error(
+                        &data,
+                        String::from("Character literals hold a single character"),
+                    )))
+                }
+            }
+        }
+        None => {
+            return Err(nom::Err::Failure(error(
+                &data,
+                String::from("Unterminated character literal"),
+            )))
+        }
+    };
+
+    if !input.starts_with(b"'") {
+        return Err(nom::Err::Failure(error(
+            &data,
+            String::from("Character literals hold a single character"),
+        )));
+    }
+
+    Ok((take(1usize)(input)?.0, value))
+}
+
+/// Parses an escape sequence starting with `\`:
+///
+/// - `\n`, `\r`, `\t` and `\0` for control characters,
+/// - `\"`, `\'` and `\\` for the characters themselves,
+/// - `\xNN` for an ASCII character in hex,
+/// - `\u{NNNN}` for any Unicode character in hex.
+fn escape(data: Span) -> IResult<Span, char, ParsingError> {
+    let (input, _) = take(1usize)(data.clone())?;
+    let Some(kind) = input.fragment().first() else {
+        return Err(nom::Err::Failure(error(
+            &data,
+            String::from("Unterminated escape sequence"),
+        )));
+    };
+    let simple = match kind {
+        b'n' => Some('\n'),
+        b'r' => Some('\r'),
+        b't' => Some('\t'),
+        b'0' => Some('\0'),
+        b'"' => Some('"'),
+        b'\'' => Some('\''),
+        b'\\' => Some('\\'),
+        _ => None,
+    };
+
+    if let Some(value) = simple {
+        return Ok((take(1usize)(input)?.0, value));
+    }
+
+    let (input, kind) = take(1usize)(input)?;
+
+    match kind.fragment()[0] {
+        b'x' => {
+            let value = input
+                .fragment()
+                .get(..2)
+                .and_then(|digits| std::str::from_utf8(digits).ok())
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .filter(u8::is_ascii);
+
+            match value {
+                Some(value) => Ok((take(2usize)(input)?.0, char::from(value))),
+                None => Err(nom::Err::Failure(error(
+                    &data,
+                    String::from(
+                        "\\x expects two hex digits up to 7F, use \\u{...} for other characters",
+                    ),
+                ))),
+            }
+        }
+        b'u' => {
+            let digits = input.fragment().strip_prefix(b"{").and_then(|rest| {
+                rest.iter()
+                    .position(|byte| *byte == b'}')
+                    .map(|end| &rest[..end])
+            });
+            let value = digits
+                .filter(|digits| (1..=6).contains(&digits.len()))
+                .and_then(|digits| std::str::from_utf8(digits).ok())
+                .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                .and_then(char::from_u32);
+
+            match (digits, value) {
+                (Some(digits), Some(value)) => Ok((take(digits.len() + 2)(input)?.0, value)),
+                _ => Err(nom::Err::Failure(error(
+                    &data,
+                    String::from("\\u expects a Unicode scalar value in hex, e.g. \\u{1F600}"),
+                ))),
+            }
+        }
+        _ => Err(nom::Err::Failure(error(
+            &data,
+            format!(
+                "Unknown escape sequence: \\{}",
+                String::from_utf8_lossy(kind.fragment())
+            ),
+        ))),
+    }
+}
+
+fn error(at: &Span, message: String) -> ParsingError {
+    ParsingError::new(
+        message,
+        at.extra
+            .find_location(at.location_offset())
+            .unwrap_or_default(),
+        ParsingErrorKind::InvalidArgument,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::Span;
+    use nano_risc_arch::SourceUnit;
+    use pretty_assertions::assert_eq;
+
+    fn string(source: &str) -> Result<String, String> {
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+
+        super::string(Span::new_extra(unit.data(), unit.clone()))
+            .map(|(_, value)| value)
+            .map_err(|err| match err {
+                nom::Err::Error(err) | nom::Err::Failure(err) => err.message().to_string(),
+                nom::Err::Incomplete(_) => unreachable!(),
+            })
+    }
+
+    fn character(source: &str) -> Result<char, String> {
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+
+        super::character(Span::new_extra(unit.data(), unit.clone()))
+            .map(|(_, value)| value)
+            .map_err(|err| match err {
+                nom::Err::Error(err) | nom::Err::Failure(err) => err.message().to_string(),
+                nom::Err::Incomplete(_) => unreachable!(),
+            })
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            string(r#""a\nb\t\"c\" \\ \x41 \u{1F600} \0""#),
+            Ok(String::from("a\nb\t\"c\" \\ A 😀 \0"))
+        );
+        assert_eq!(string("\"héllo\""), Ok(String::from("héllo")));
+        assert_eq!(
+            string(r#""\q""#),
+            Err(String::from("Unknown escape sequence: \\q"))
+        );
+        assert!(string(r#""\xff""#).is_err());
+        assert!(string(r#""\u{110000}""#).is_err());
+        assert_eq!(string("\"open"), Err(String::from("Unterminated string")));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let unit = SourceUnit::new_anonymous(vec![b'"', 0xff, b'"']);
+        let result = super::string(Span::new_extra(unit.data(), unit.clone()));
+
+        assert!(matches!(result, Err(nom::Err::Failure(_))));
+    }
+
+    #[test]
+    fn characters() {
+        assert_eq!(character("'a'"), Ok('a'));
+        assert_eq!(character(r"'\n'"), Ok('\n'));
+        assert_eq!(character(r"'\''"), Ok('\''));
+        assert_eq!(character("'é'"), Ok('é'));
+        assert_eq!(
+            character("'ab'"),
+            Err(String::from("Character literals hold a single character"))
+        );
+        assert!(character("''").is_err());
+    }
+}
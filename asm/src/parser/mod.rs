@@ -1,21 +1,34 @@
 mod argument_token;
+mod binary_operator;
 mod comment;
+mod data_kind;
+mod directive;
+mod expression;
+mod expression_token;
+mod file_resolver;
 mod instruction;
 mod label;
+mod literal;
 mod parsing_error;
 mod parsing_error_kind;
 mod register;
 mod token;
 mod token_kind;
+mod unary_operator;
 
 pub use argument_token::ArgumentToken;
+pub use binary_operator::BinaryOperator;
+pub use data_kind::DataKind;
+pub use expression_token::ExpressionToken;
+pub use file_resolver::{FileResolver, FsResolver, MemoryResolver};
+pub(crate) use literal::string_offset;
 use nano_risc_arch::SourceUnit;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{alpha1, alphanumeric1, multispace0},
+    character::complete::{alpha1, alphanumeric1, line_ending, multispace0, not_line_ending},
     combinator::{eof, recognize},
-    multi::{many0_count, many_till},
+    multi::many0_count,
     sequence::{pair, preceded},
     Finish, IResult,
 };
@@ -24,12 +37,113 @@ pub use parsing_error::ParsingError;
 pub use parsing_error_kind::ParsingErrorKind;
 pub use token::Token;
 pub use token_kind::TokenKind;
+pub use unary_operator::UnaryOperator;
 pub(crate) type Span<'a> = LocatedSpan<&'a [u8], SourceUnit>;
 
-pub fn parse(unit: &SourceUnit) -> Result<Vec<Token>, ParsingError> {
+/// Parses `unit`, a line that can't be parsed is reported and skipped, so
+/// every error in the file is returned at once.
+pub fn parse(unit: &SourceUnit) -> Result<Vec<Token>, Vec<ParsingError>> {
     parse_inner(unit)
 }
 
+/// Parses `unit` and every file it includes with `.include`. A file is
+/// included only once, later includes of it are skipped.
+///
+/// Returns all the loaded files with `unit` first, even when parsing
+/// failed. Locations of tokens and errors point into them with
+/// [`nano_risc_arch::Location::unit`].
+pub fn parse_with_includes(
+    unit: &SourceUnit,
+    resolver: &dyn FileResolver,
+) -> (Vec<SourceUnit>, Result<Vec<Token>, Vec<ParsingError>>) {
+    let mut units = vec![unit.clone()];
+    let mut errors = Vec::new();
+    let tokens = include(&mut units, 0, resolver, &mut vec![0], &mut errors);
+
+    if !errors.is_empty() {
+        return (units, Err(errors));
+    }
+
+    (units, Ok(tokens))
+}
+
+/// Parses `units[index]` and replaces its includes with their tokens,
+/// `stack` holds the files being parsed to detect cycles.
+fn include(
+    units: &mut Vec<SourceUnit>,
+    index: usize,
+    resolver: &dyn FileResolver,
+    stack: &mut Vec<usize>,
+    errors: &mut Vec<ParsingError>,
+) -> Vec<Token> {
+    let unit = units[index].clone();
+    let tokens = match parse_inner(&unit) {
+        Ok(tokens) => tokens,
+        Err(unit_errors) => {
+            errors.extend(unit_errors.into_iter().map(|err| err.in_unit(index)));
+            return Vec::new();
+        }
+    };
+    let mut output = Vec::with_capacity(tokens.len());
+
+    for mut token in tokens {
+        token.location.unit = index;
+
+        let TokenKind::Include { path } = &token.kind else {
+            output.push(token);
+            continue;
+        };
+        let included = match resolver.resolve(&unit, path) {
+            Ok(included) => included,
+            Err(message) => {
+                errors.push(ParsingError::new(
+                    message,
+                    token.location,
+                    ParsingErrorKind::IncludeNotFound,
+                ));
+                continue;
+            }
+        };
+        let included = match units.iter().position(|unit| unit.name() == included.name()) {
+            Some(included) if stack.contains(&included) => {
+                let chain: Vec<&str> = stack
+                    .iter()
+                    .chain([&included])
+                    .map(|unit| units[*unit].name())
+                    .collect();
+
+                errors.push(ParsingError::new(
+                    format!("Include cycle: {}", chain.join(" -> ")),
+                    token.location,
+                    ParsingErrorKind::IncludeCycle,
+                ));
+                continue;
+            }
+            // The file was already included through another one.
+            Some(_) => continue,
+            None => {
+                units.push(included);
+                units.len() - 1
+            }
+        };
+
+        stack.push(included);
+        output.append(&mut include(units, included, resolver, stack, errors));
+        stack.pop();
+    }
+
+    output
+}
+
+/// A label name, local labels are written as `@name`.
+pub(crate) fn label_name(data: Span) -> IResult<Span, String, ParsingError> {
+    alt((
+        recognize(preceded(tag("@"), identifier)),
+        recognize(identifier),
+    ))(data)
+    .map(|(remain, name)| (remain, String::from_utf8(name.fragment().to_vec()).unwrap()))
+}
+
 pub(crate) fn identifier(data: Span) -> IResult<Span, String, ParsingError> {
     recognize(pair(
         alt((alpha1, tag("_"))),
@@ -38,30 +152,79 @@ pub(crate) fn identifier(data: Span) -> IResult<Span, String, ParsingError> {
     .map(|(remain, name)| (remain, String::from_utf8(name.fragment().to_vec()).unwrap()))
 }
 
-fn parse_inner(unit: &SourceUnit) -> Result<Vec<Token>, ParsingError> {
-    many_till(
-        alt((
-            preceded(multispace0, label::parse),
-            preceded(multispace0, instruction::parse),
-            preceded(multispace0, comment::parse),
-        )),
-        alt((eof, preceded(multispace0, eof))),
-    )(Span::new_extra(unit.data(), unit.clone()))
-    .finish()
-    .map(|(_, (tokens, _))| tokens.concat())
-    .map_err(|err| -> ParsingError {
-        ParsingError::wrap(
-            String::from("Expected label, instruction or comment"),
-            err.location(),
-            ParsingErrorKind::Unknown,
-            err.inner().cloned().unwrap(),
-        )
+fn parse_inner(unit: &SourceUnit) -> Result<Vec<Token>, Vec<ParsingError>> {
+    let mut data = Span::new_extra(unit.data(), unit.clone());
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+
+    loop {
+        let (line, _) = multispace0::<Span, ParsingError>(data).unwrap();
+
+        if line.is_empty() {
+            break;
+        }
+
+        match line_tokens(line.clone()) {
+            Ok((remain, mut line_tokens)) => {
+                tokens.append(&mut line_tokens);
+                data = remain;
+            }
+            Err(error) => {
+                errors.push(error);
+                data = skip_line(line);
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(comment::attach_docs(tokens))
+}
+
+/// Parses the tokens at the start of `data`. When nothing matches, the error
+/// of the parser that got the furthest is the most specific one, failures
+/// are specific wherever they point.
+fn line_tokens(data: Span) -> Result<(Span, Vec<Token>), ParsingError> {
+    let start = data
+        .extra
+        .find_location(data.location_offset())
+        .unwrap_or_default();
+
+    alt((
+        directive::parse,
+        label::parse,
+        instruction::parse,
+        comment::parse,
+    ))(data)
+    .map_err(|error| match error {
+        nom::Err::Error(error) if error.location().offset <= start.offset => {
+            nom::Err::Error(ParsingError::new(
+                String::from("Expected label, instruction, directive or comment"),
+                start,
+                ParsingErrorKind::Unknown,
+            ))
+        }
+        error => error,
     })
+    .finish()
+}
+
+/// Skips the rest of the line where parsing failed.
+fn skip_line(data: Span) -> Span {
+    let (remain, _) = pair(
+        not_line_ending::<Span, ParsingError>,
+        alt((line_ending, eof)),
+    )(data)
+    .unwrap();
+
+    remain
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::parser::{self, ArgumentToken, Token, TokenKind};
+    use crate::parser::{self, ArgumentToken, MemoryResolver, ParsingErrorKind, Token, TokenKind};
     use nano_risc_arch::{Location, RegisterKind, RegisterMode, SourceUnit};
     use pretty_assertions::assert_eq;
 
@@ -91,7 +254,8 @@ mov $r0 %r1
                     location: Location {
                         line: 2,
                         column: 1,
-                        offset: 1
+                        offset: 1,
+                        ..Location::default()
                     },
                     kind: TokenKind::Comment {
                         text: String::from("A basic program")
@@ -101,7 +265,8 @@ mov $r0 %r1
                     location: Location {
                         line: 3,
                         column: 1,
-                        offset: 19
+                        offset: 19,
+                        ..Location::default()
                     },
                     kind: TokenKind::Label {
                         name: String::from("start")
@@ -111,7 +276,8 @@ mov $r0 %r1
                     location: Location {
                         line: 4,
                         column: 1,
-                        offset: 26
+                        offset: 26,
+                        ..Location::default()
                     },
                     kind: TokenKind::Operation {
                         operation: String::from("add"),
@@ -121,7 +287,8 @@ mov $r0 %r1
                     location: Location {
                         line: 4,
                         column: 5,
-                        offset: 30
+                        offset: 30,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Register {
@@ -136,7 +303,8 @@ mov $r0 %r1
                     location: Location {
                         line: 4,
                         column: 9,
-                        offset: 34
+                        offset: 34,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Int { value: 1 }
@@ -146,7 +314,8 @@ mov $r0 %r1
                     location: Location {
                         line: 4,
                         column: 11,
-                        offset: 36
+                        offset: 36,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Int { value: 0 }
@@ -156,7 +325,8 @@ mov $r0 %r1
                     location: Location {
                         line: 5,
                         column: 1,
-                        offset: 38
+                        offset: 38,
+                        ..Location::default()
                     },
                     kind: TokenKind::Operation {
                         operation: String::from("sub"),
@@ -166,7 +336,8 @@ mov $r0 %r1
                     location: Location {
                         line: 5,
                         column: 5,
-                        offset: 42
+                        offset: 42,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Register {
@@ -181,7 +352,8 @@ mov $r0 %r1
                     location: Location {
                         line: 5,
                         column: 9,
-                        offset: 46
+                        offset: 46,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Register {
@@ -196,7 +368,8 @@ mov $r0 %r1
                     location: Location {
                         line: 7,
                         column: 1,
-                        offset: 51
+                        offset: 51,
+                        ..Location::default()
                     },
                     kind: TokenKind::Comment {
                         text: String::from("Jump")
@@ -206,7 +379,8 @@ mov $r0 %r1
                     location: Location {
                         line: 8,
                         column: 1,
-                        offset: 58
+                        offset: 58,
+                        ..Location::default()
                     },
                     kind: TokenKind::Operation {
                         operation: String::from("jmp"),
@@ -216,7 +390,8 @@ mov $r0 %r1
                     location: Location {
                         line: 8,
                         column: 5,
-                        offset: 62
+                        offset: 62,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Label {
@@ -228,7 +403,8 @@ mov $r0 %r1
                     location: Location {
                         line: 10,
                         column: 1,
-                        offset: 69
+                        offset: 69,
+                        ..Location::default()
                     },
                     kind: TokenKind::Comment {
                         text: String::from("Print stack pointer and program counter")
@@ -238,7 +414,8 @@ mov $r0 %r1
                     location: Location {
                         line: 11,
                         column: 1,
-                        offset: 111
+                        offset: 111,
+                        ..Location::default()
                     },
                     kind: TokenKind::Operation {
                         operation: String::from("dbg"),
@@ -248,7 +425,8 @@ mov $r0 %r1
                     location: Location {
                         line: 11,
                         column: 5,
-                        offset: 115
+                        offset: 115,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Register {
@@ -260,7 +438,8 @@ mov $r0 %r1
                     location: Location {
                         line: 12,
                         column: 1,
-                        offset: 119
+                        offset: 119,
+                        ..Location::default()
                     },
                     kind: TokenKind::Operation {
                         operation: String::from("dbg"),
@@ -270,7 +449,8 @@ mov $r0 %r1
                     location: Location {
                         line: 12,
                         column: 5,
-                        offset: 123
+                        offset: 123,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Register {
@@ -283,6 +463,7 @@ mov $r0 %r1
                         line: 14,
                         column: 1,
                         offset: 128,
+                        ..Location::default()
                     },
                     kind: TokenKind::Operation {
                         operation: String::from("mov")
@@ -293,6 +474,7 @@ mov $r0 %r1
                         line: 14,
                         column: 5,
                         offset: 132,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Register {
@@ -308,6 +490,7 @@ mov $r0 %r1
                         line: 14,
                         column: 9,
                         offset: 136,
+                        ..Location::default()
                     },
                     kind: TokenKind::Argument {
                         argument: ArgumentToken::Register {
@@ -321,4 +504,131 @@ mov $r0 %r1
             ])
         )
     }
+
+    #[test]
+    fn includes() {
+        let mut resolver = MemoryResolver::new();
+
+        resolver.add_file("lib/math.asm", b"double:\n.include \"ret.asm\"".to_vec());
+        resolver.add_file("lib/ret.asm", b"ret".to_vec());
+
+        let unit = SourceUnit::new(
+            String::from("main.asm"),
+            b"halt\n.include \"lib/math.asm\"".to_vec(),
+        );
+        let (units, tokens) = parser::parse_with_includes(&unit, &resolver);
+        let tokens = tokens.unwrap();
+        let names: Vec<&str> = units.iter().map(|unit| unit.name()).collect();
+
+        assert_eq!(names, ["main.asm", "lib/math.asm", "lib/ret.asm"]);
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    location: Location::new(1, 1, 0),
+                    kind: TokenKind::Operation {
+                        operation: String::from("halt")
+                    }
+                },
+                Token {
+                    location: Location {
+                        unit: 1,
+                        ..Location::new(1, 1, 0)
+                    },
+                    kind: TokenKind::Label {
+                        name: String::from("double")
+                    }
+                },
+                Token {
+                    location: Location {
+                        unit: 2,
+                        ..Location::new(1, 1, 0)
+                    },
+                    kind: TokenKind::Operation {
+                        operation: String::from("ret")
+                    }
+                },
+            ]
+        );
+
+        resolver.add_file("lib/ret.asm", b".include \"math.asm\"".to_vec());
+
+        let errors = parser::parse_with_includes(&unit, &resolver).1.unwrap_err();
+        let error = &errors[0];
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(error.kind(), ParsingErrorKind::IncludeCycle);
+        assert_eq!(error.location().unit, 2);
+
+        let unit = SourceUnit::new(
+            String::from("main.asm"),
+            b".include \"missing.asm\"".to_vec(),
+        );
+
+        assert_eq!(
+            parser::parse_with_includes(&unit, &resolver)
+                .1
+                .map_err(|errors| errors.iter().map(|err| err.kind()).collect()),
+            Err(vec![ParsingErrorKind::IncludeNotFound])
+        );
+    }
+
+    #[test]
+    fn include_once() {
+        let mut resolver = MemoryResolver::new();
+
+        resolver.add_file("left.asm", b".include \"shared.asm\"".to_vec());
+        resolver.add_file("right.asm", b".include \"shared.asm\"".to_vec());
+        resolver.add_file("shared.asm", b"shared:\nret".to_vec());
+
+        let unit = SourceUnit::new(
+            String::from("main.asm"),
+            b".include \"left.asm\"\n.include \"right.asm\"".to_vec(),
+        );
+        let (units, tokens) = parser::parse_with_includes(&unit, &resolver);
+        let kinds: Vec<TokenKind> = tokens
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect();
+        let names: Vec<&str> = units.iter().map(|unit| unit.name()).collect();
+
+        assert_eq!(names, ["main.asm", "left.asm", "shared.asm", "right.asm"]);
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Label {
+                    name: String::from("shared")
+                },
+                TokenKind::Operation {
+                    operation: String::from("ret")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn recovery() {
+        let src = r#"
+add $r0 1 2
+mov $r0 !
+!!!
+.equ
+jmp start
+"#;
+        let errors = parser::parse(&SourceUnit::new_anonymous(src.as_bytes().to_vec()))
+            .unwrap_err()
+            .iter()
+            .map(|err| (err.location().line, err.location().column, err.kind()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                (3, 9, ParsingErrorKind::InvalidArgument),
+                (4, 1, ParsingErrorKind::Unknown),
+                (5, 5, ParsingErrorKind::InvalidDirective),
+            ]
+        );
+    }
 }
@@ -33,7 +33,23 @@ impl<'s> ParseError<Span<'s>> for ParsingError {
         }
     }
 
+    /// Keeps the error of the alternative that got the furthest, it's the
+    /// most specific one.
+    fn or(self, other: Self) -> Self {
+        if self.location.offset > other.location.offset {
+            self
+        } else {
+            other
+        }
+    }
+
     fn append(input: Span, kind: ErrorKind, other: Self) -> Self {
+        // Combinators like `alt` and `many_till` report their errors at the
+        // start of their input, which hides where the parsing actually failed.
+        if other.location.offset >= input.location_offset() {
+            return other;
+        }
+
         Self {
             message: format!("Parsing error: {kind:#?}"),
             location: input
@@ -85,6 +101,13 @@ impl ParsingError {
         self.inner.as_ref().map(|v| v.as_ref())
     }
 
+    /// Points the error and its inner errors into the file `unit`.
+    pub(crate) fn in_unit(mut self, unit: usize) -> Self {
+        self.location.unit = unit;
+        self.inner = self.inner.map(|inner| Box::new(inner.in_unit(unit)));
+        self
+    }
+
     pub(crate) fn from_nom_error(
         message: String,
         error: nom::Err<Self>,
@@ -93,7 +116,8 @@ impl ParsingError {
         match error {
             nom::Err::Incomplete(_) => error,
             nom::Err::Error(inner) => nom::Err::Error(Self::new(message, inner.location, kind)),
-            nom::Err::Failure(inner) => nom::Err::Failure(Self::new(message, inner.location, kind)),
+            // Failures are specific, e.g. an integer that doesn't fit.
+            nom::Err::Failure(_) => error,
         }
     }
 }
@@ -6,5 +6,9 @@ pub enum ParsingErrorKind {
     InvalidComment,
     InvalidRegister,
     InvalidArgument,
+    IntegerOverflow,
+    InvalidDirective,
+    IncludeNotFound,
+    IncludeCycle,
     Unknown,
 }
@@ -7,10 +7,10 @@ use nom::{
 use super::{ParsingError, ParsingErrorKind, Span};
 
 pub fn parse(data: Span) -> IResult<Span, RegisterKind, ParsingError> {
-    alt((regular_register, program_counter, stack_pointer))(data).map_err(
+    alt((regular_register, program_counter, stack_pointer, clock))(data).map_err(
         |err: nom::Err<ParsingError>| {
             ParsingError::from_nom_error(
-                String::from("Expected a regular register, pc or sp"),
+                String::from("Expected a regular register, pc, sp or clk"),
                 err,
                 ParsingErrorKind::InvalidRegister,
             )
@@ -53,3 +53,7 @@ fn program_counter(data: Span) -> IResult<Span, RegisterKind, ParsingError> {
 fn stack_pointer(data: Span) -> IResult<Span, RegisterKind, ParsingError> {
     recognize(tag("$sp"))(data).map(|(remain, _)| (remain, RegisterKind::StackPointer))
 }
+
+fn clock(data: Span) -> IResult<Span, RegisterKind, ParsingError> {
+    recognize(tag("$clk"))(data).map(|(remain, _)| (remain, RegisterKind::Clock))
+}
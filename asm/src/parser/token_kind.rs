@@ -1,9 +1,22 @@
-use super::ArgumentToken;
+use super::{ArgumentToken, DataKind};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenKind {
     Comment { text: String },
+    // A `##` comment documenting the label right after it, consecutive lines
+    // are joined with `\n`.
+    DocComment { text: String },
     Label { name: String },
     Operation { operation: String },
     Argument { argument: ArgumentToken },
+    // Starts a macro definition, see `crate::compiler::expand_macros`.
+    Macro { name: String, params: Vec<String> },
+    EndMacro,
+    // Pulls in another file, see `crate::parser::parse_with_includes`.
+    Include { path: String },
+    // Defines a constant with `.equ`, followed by its value argument.
+    Constant { name: String },
+    // Emits data into the text section, followed by its values. Labels right
+    // before it resolve to the data's address.
+    Data { kind: DataKind },
 }
@@ -0,0 +1,26 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOperator {
+    Negate,
+    Not,
+}
+
+impl UnaryOperator {
+    /// Returns `None` when the result overflows.
+    pub fn apply(self, value: i32) -> Option<i32> {
+        match self {
+            UnaryOperator::Negate => value.checked_neg(),
+            UnaryOperator::Not => Some(!value),
+        }
+    }
+}
+
+impl Display for UnaryOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnaryOperator::Negate => write!(f, "-"),
+            UnaryOperator::Not => write!(f, "~"),
+        }
+    }
+}
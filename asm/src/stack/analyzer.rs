@@ -0,0 +1,328 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use nano_risc_arch::{Argument, Assembly, Limits, Location, Operation, RegisterKind};
+
+use super::{frame::Frame, StackIssue, StackIssueKind, StackReport};
+
+/// Labels the test runner calls into, except local labels like
+/// `test_count@loop`.
+const TEST_PREFIX: &str = "test_";
+
+pub(super) struct Analyzer<'a> {
+    assembly: &'a Assembly,
+    limits: &'a Limits,
+    /// Deepest point of every analyzed function relative to its caller,
+    /// counting the return address.
+    functions: BTreeMap<usize, Option<usize>>,
+    /// Functions being walked, calling one of them again is recursion.
+    active: BTreeSet<usize>,
+    coroutines: Vec<usize>,
+    issues: Vec<StackIssue>,
+}
+
+impl<'a> Analyzer<'a> {
+    pub(super) fn new(assembly: &'a Assembly, limits: &'a Limits) -> Self {
+        Self {
+            assembly,
+            limits,
+            functions: BTreeMap::new(),
+            active: BTreeSet::new(),
+            coroutines: Vec::new(),
+            issues: Vec::new(),
+        }
+    }
+
+    pub(super) fn run(mut self) -> StackReport {
+        let mut max_depth = Some(0);
+        let mut merge = |depth: Option<usize>| {
+            max_depth = max_depth.zip(depth).map(|(a, b)| a.max(b));
+        };
+
+        if !self.assembly.instructions.is_empty() {
+            merge(self.walk(0, Frame::Main));
+        }
+
+        let tests: Vec<usize> = self
+            .assembly
+            .debug_info
+            .iter()
+            .flat_map(|info| &info.labels)
+            .filter(|(name, _)| name.starts_with(TEST_PREFIX) && !name.contains('@'))
+            .map(|(_, address)| *address)
+            .collect();
+
+        for test in tests {
+            merge(self.function(test, None));
+        }
+
+        let mut spawned = BTreeSet::new();
+
+        while let Some(entry) = self.coroutines.pop() {
+            if spawned.insert(entry) {
+                merge(self.walk(entry, Frame::Coroutine));
+            }
+        }
+
+        self.issues.sort_by_key(|issue| issue.address());
+
+        StackReport::new(max_depth, self.issues)
+    }
+
+    /// Deepest point reached by calling `entry`, `None` when it can't be
+    /// determined.
+    fn function(&mut self, entry: usize, call: Option<usize>) -> Option<usize> {
+        if let Some(depth) = self.functions.get(&entry) {
+            return *depth;
+        }
+
+        if self.active.contains(&entry) {
+            if let Some(call) = call {
+                self.report(
+                    call,
+                    format!(
+                        "Recursive call to {}, the stack depth can't be determined",
+                        self.name(entry)
+                    ),
+                    StackIssueKind::Indeterminate,
+                );
+            }
+
+            return None;
+        }
+
+        self.active.insert(entry);
+        let depth = self.walk(entry, Frame::Function);
+        self.active.remove(&entry);
+        self.functions.insert(entry, depth);
+
+        depth
+    }
+
+    /// Follows every path from `entry` and returns the deepest point, or
+    /// `None` when some path can't be followed.
+    fn walk(&mut self, entry: usize, frame: Frame) -> Option<usize> {
+        let mut depths: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut queue = vec![(entry, frame.base())];
+        let mut max_depth = frame.base();
+        let mut exact = true;
+
+        while let Some((address, depth)) = queue.pop() {
+            let Some(instruction) = self.assembly.instructions.get(address) else {
+                continue;
+            };
+
+            match depths.get(&address) {
+                Some(seen) if *seen == depth => continue,
+                Some(seen) => {
+                    let (low, high) = (depth.min(*seen), depth.max(*seen));
+
+                    self.report(
+                        address,
+                        format!("Paths reach this instruction with {low} and {high} values on the stack"),
+                        StackIssueKind::Unbalanced,
+                    );
+                    exact = false;
+                    continue;
+                }
+                None => {
+                    depths.insert(address, depth);
+                }
+            }
+
+            max_depth = max_depth.max(depth);
+
+            let operation = instruction.operation;
+            let arguments = &instruction.arguments;
+            let next = address + 1;
+
+            if operation.writes_first_argument()
+                && matches!(
+                    arguments.first(),
+                    Some(Argument::Register {
+                        register: RegisterKind::ProgramCounter
+                    })
+                )
+            {
+                self.report(
+                    address,
+                    format!("{operation} writes to $pc, the stack depth can't be followed"),
+                    StackIssueKind::Indeterminate,
+                );
+                exact = false;
+                continue;
+            }
+
+            match operation {
+                Operation::Push => {
+                    if depth + 1 > self.limits.stack_size {
+                        self.overflow(address, depth + 1, String::from("push"));
+                    } else {
+                        queue.push((next, depth + 1));
+                    }
+                }
+                Operation::Pop | Operation::Peek => {
+                    if depth == 0 {
+                        self.report(
+                            address,
+                            format!("{operation} on an empty stack"),
+                            StackIssueKind::Underflow,
+                        );
+                    } else if operation == Operation::Pop {
+                        queue.push((next, depth - 1));
+                    } else {
+                        queue.push((next, depth));
+                    }
+                }
+                Operation::Jmp => match self.target(arguments.first()) {
+                    Some(target) => queue.push((target, depth)),
+                    None => {
+                        self.computed(address, operation);
+                        exact = false;
+                    }
+                },
+                _ if operation.is_conditional_branch() => {
+                    match self.target(arguments.last()) {
+                        Some(target) => queue.push((target, depth)),
+                        None => {
+                            self.computed(address, operation);
+                            exact = false;
+                        }
+                    }
+
+                    queue.push((next, depth));
+                }
+                Operation::Call => {
+                    match self.target(arguments.first()) {
+                        Some(target) => match self.function(target, Some(address)) {
+                            Some(inner) => {
+                                let peak = depth + inner;
+
+                                max_depth = max_depth.max(peak);
+
+                                if peak > self.limits.stack_size {
+                                    self.overflow(
+                                        address,
+                                        peak,
+                                        format!("call to {}", self.name(target)),
+                                    );
+                                }
+                            }
+                            None => exact = false,
+                        },
+                        None => {
+                            self.computed(address, operation);
+                            exact = false;
+                        }
+                    }
+
+                    queue.push((next, depth));
+                }
+                Operation::Ret => match (frame, depth) {
+                    (Frame::Coroutine, 0) | (Frame::Function, 1) => {}
+                    (_, 0) => self.report(
+                        address,
+                        String::from("ret on an empty stack"),
+                        StackIssueKind::Underflow,
+                    ),
+                    (Frame::Function, _) => {
+                        self.report(
+                            address,
+                            format!(
+                                "ret leaves {} pushed values on the stack and returns to the last one",
+                                depth - 1
+                            ),
+                            StackIssueKind::Unbalanced,
+                        );
+                        exact = false;
+                    }
+                    (Frame::Main | Frame::Coroutine, _) => {
+                        self.report(
+                            address,
+                            String::from(
+                                "ret returns to a pushed value, the stack depth can't be followed",
+                            ),
+                            StackIssueKind::Indeterminate,
+                        );
+                        exact = false;
+                    }
+                },
+                Operation::Halt => {}
+                Operation::Spawn => {
+                    match self.target(arguments.get(1)) {
+                        Some(target) => self.coroutines.push(target),
+                        None => {
+                            self.computed(address, operation);
+                            exact = false;
+                        }
+                    }
+
+                    queue.push((next, depth));
+                }
+                _ => queue.push((next, depth)),
+            }
+        }
+
+        exact.then_some(max_depth)
+    }
+
+    fn target(&self, argument: Option<&Argument>) -> Option<usize> {
+        match argument {
+            Some(Argument::Int { value }) => usize::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    /// Names `address` after the label pointing to it.
+    fn name(&self, address: usize) -> String {
+        match self
+            .assembly
+            .debug_info
+            .as_ref()
+            .and_then(|info| info.label_for(address))
+        {
+            Some((name, label)) if label == address => name.to_string(),
+            _ => format!("address {address}"),
+        }
+    }
+
+    fn location(&self, address: usize) -> Option<Location> {
+        self.assembly
+            .debug_info
+            .as_ref()
+            .and_then(|info| info.source_loc.get(&address))
+            .copied()
+    }
+
+    fn overflow(&mut self, address: usize, depth: usize, what: String) {
+        self.report(
+            address,
+            format!(
+                "{what} can grow the stack to {depth} values (maximum: {})",
+                self.limits.stack_size
+            ),
+            StackIssueKind::Overflow { depth },
+        );
+    }
+
+    fn computed(&mut self, address: usize, operation: Operation) {
+        self.report(
+            address,
+            format!("{operation} to a computed address, the stack depth can't be followed"),
+            StackIssueKind::Indeterminate,
+        );
+    }
+
+    fn report(&mut self, address: usize, message: String, kind: StackIssueKind) {
+        let reported = self
+            .issues
+            .iter()
+            .any(|issue| issue.address() == address && *issue.kind() == kind);
+
+        if !reported {
+            let location = self.location(address);
+
+            self.issues
+                .push(StackIssue::new(message, address, location, kind));
+        }
+    }
+}
@@ -0,0 +1,20 @@
+/// What started the code being walked, decides what `ret` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Frame {
+    /// The program's entry point, `ret` on an empty stack fails.
+    Main,
+    /// A spawned coroutine, `ret` on an empty stack finishes it.
+    Coroutine,
+    /// A `call` target, the return address is on the stack at entry.
+    Function,
+}
+
+impl Frame {
+    /// Values on the stack when the frame starts.
+    pub(super) fn base(self) -> usize {
+        match self {
+            Frame::Main | Frame::Coroutine => 0,
+            Frame::Function => 1,
+        }
+    }
+}
@@ -0,0 +1,172 @@
+mod analyzer;
+mod frame;
+mod stack_issue;
+mod stack_issue_kind;
+mod stack_report;
+
+use nano_risc_arch::{Assembly, Limits};
+
+use analyzer::Analyzer;
+
+pub use stack_issue::StackIssue;
+pub use stack_issue_kind::StackIssueKind;
+pub use stack_report::StackReport;
+
+/// Follows every path through `push`, `pop`, `peek`, `call` and `ret` from
+/// the entry point, `test_` labels and spawned coroutines, and reports the
+/// deepest the stack can get along with paths that overflow
+/// `limits.stack_size`, underflow or can't be followed statically.
+pub fn verify(assembly: &Assembly, limits: &Limits) -> StackReport {
+    Analyzer::new(assembly, limits).run()
+}
+
+#[cfg(test)]
+mod tests {
+    use nano_risc_arch::{Limits, SourceUnit};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{compiler, parser};
+
+    fn report(source: &str, limits: &Limits) -> StackReport {
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+        let assembly = compiler::compile(unit, tokens, limits).unwrap();
+
+        verify(&assembly, limits)
+    }
+
+    fn issues(report: &StackReport) -> Vec<(usize, StackIssueKind)> {
+        report
+            .issues()
+            .iter()
+            .map(|issue| (issue.location().unwrap().line, issue.kind().clone()))
+            .collect()
+    }
+
+    #[test]
+    fn balanced() {
+        let report = report(
+            r#"
+                push 1
+                call inner
+                pop $r0
+                halt
+            inner:
+                push 2
+                push 3
+                pop $r1
+                pop $r1
+                ret
+            "#,
+            &Limits::default(),
+        );
+
+        assert_eq!(report.max_depth(), Some(4));
+        assert_eq!(issues(&report), vec![]);
+    }
+
+    #[test]
+    fn overflow_and_underflow() {
+        let limits = Limits {
+            stack_size: 2,
+            ..Limits::default()
+        };
+        let report = report(
+            r#"
+                beqz $r0 deep
+                pop $r0
+                halt
+            deep:
+                push 1
+                call inner
+                halt
+            inner:
+                push 2
+                pop $r0
+                ret
+            "#,
+            &limits,
+        );
+
+        assert_eq!(report.max_depth(), Some(3));
+        assert_eq!(
+            issues(&report),
+            vec![
+                (3, StackIssueKind::Underflow),
+                (7, StackIssueKind::Overflow { depth: 3 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn unbalanced_loop() {
+        let report = report(
+            r#"
+            loop:
+                push 1
+                jmp loop
+            "#,
+            &Limits::default(),
+        );
+
+        assert_eq!(report.max_depth(), None);
+        assert_eq!(issues(&report), vec![(3, StackIssueKind::Unbalanced)]);
+    }
+
+    #[test]
+    fn indeterminate() {
+        let computed = report(
+            r#"
+                mov $r0 done
+                jmp $r0
+            done:
+                halt
+            "#,
+            &Limits::default(),
+        );
+
+        assert_eq!(computed.max_depth(), None);
+        assert_eq!(issues(&computed), vec![(3, StackIssueKind::Indeterminate)]);
+
+        let recursive = report(
+            r#"
+                call recurse
+                halt
+            recurse:
+                push 1
+                call recurse
+                pop $r0
+                ret
+            "#,
+            &Limits::default(),
+        );
+
+        assert_eq!(recursive.max_depth(), None);
+        assert_eq!(issues(&recursive), vec![(6, StackIssueKind::Indeterminate)]);
+    }
+
+    #[test]
+    fn coroutines_and_tests() {
+        let report = report(
+            r#"
+                spawn $r0 worker
+                halt
+            worker:
+                push 1
+                push 2
+                pop $r1
+                pop $r1
+                ret
+            test_push:
+                push 1
+                pop $r0
+                ret
+            "#,
+            &Limits::default(),
+        );
+
+        assert_eq!(report.max_depth(), Some(2));
+        assert_eq!(issues(&report), vec![]);
+    }
+}
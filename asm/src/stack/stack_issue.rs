@@ -0,0 +1,44 @@
+use nano_risc_arch::Location;
+
+use super::StackIssueKind;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackIssue {
+    message: String,
+    address: usize,
+    location: Option<Location>,
+    kind: StackIssueKind,
+}
+
+impl StackIssue {
+    pub fn new(
+        message: String,
+        address: usize,
+        location: Option<Location>,
+        kind: StackIssueKind,
+    ) -> Self {
+        Self {
+            message,
+            address,
+            location,
+            kind,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Address of the instruction the issue was found at.
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    pub fn location(&self) -> Option<Location> {
+        self.location
+    }
+
+    pub fn kind(&self) -> &StackIssueKind {
+        &self.kind
+    }
+}
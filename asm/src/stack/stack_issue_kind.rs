@@ -0,0 +1,12 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StackIssueKind {
+    /// A path pushes more values than `Limits::stack_size`.
+    Overflow { depth: usize },
+    /// A path pops a value that was never pushed.
+    Underflow,
+    /// Paths reaching the same instruction leave a different number of
+    /// values on the stack, or a function returns with extra values.
+    Unbalanced,
+    /// The depth depends on values only known at runtime.
+    Indeterminate,
+}
@@ -0,0 +1,23 @@
+use super::StackIssue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackReport {
+    max_depth: Option<usize>,
+    issues: Vec<StackIssue>,
+}
+
+impl StackReport {
+    pub fn new(max_depth: Option<usize>, issues: Vec<StackIssue>) -> Self {
+        Self { max_depth, issues }
+    }
+
+    /// Deepest the stack of any coroutine gets, `None` when some path
+    /// couldn't be followed statically.
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    pub fn issues(&self) -> &[StackIssue] {
+        &self.issues
+    }
+}
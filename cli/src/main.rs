@@ -1,37 +1,381 @@
-use std::{fs, path::PathBuf};
+use std::{
+    env, fs,
+    io::{self, IsTerminal},
+    path::PathBuf,
+    process, thread,
+    time::{Duration, Instant},
+};
 
-use clap::Parser;
-use nano_risc_arch::{Limits, SourceUnit};
-use nano_risc_asm::{compiler, parser};
-use nano_risc_vm::{VMStatus, VM};
+use clap::{Parser, Subcommand};
+use nano_risc_arch::{Assembly, Limits, Location, SourceUnit};
+use nano_risc_asm::{
+    compiler,
+    diagnostics::{Diagnostic, Renderer},
+    lints::{Lint, LintConfig},
+    parser::{self, FsResolver},
+    stack,
+};
+use nano_risc_vm::{DebugOutput, ProfileReport, StdoutHost, TestOutcome, TestRunner, VMStatus, VM};
 
 #[derive(Debug, Clone, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub run: Option<RunArgs>,
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct RunArgs {
     /// Path to an assembly file
     pub assembly: PathBuf,
+    /// Print a table of the hottest labels, functions and instructions
+    #[arg(long)]
+    pub profile: bool,
+    /// Write an lcov coverage report to the given path
+    #[arg(long)]
+    pub coverage: Option<PathBuf>,
+    /// Check how deep the stack can get before running
+    #[arg(long)]
+    pub check_stack: bool,
+    /// Silence the warnings of a lint, can be repeated
+    #[arg(long, value_name = "LINT")]
+    pub allow: Vec<Lint>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Run every label starting with `test_` in a fresh VM
+    Test {
+        /// Path to an assembly file
+        assembly: PathBuf,
+        /// Fail a test that executes more instructions than this
+        #[arg(long, default_value_t = 1_000_000)]
+        max_ticks: u64,
+        /// Silence the warnings of a lint, can be repeated
+        #[arg(long, value_name = "LINT")]
+        allow: Vec<Lint>,
+    },
 }
 
 fn main() {
     let app = Args::parse();
-    let unit = SourceUnit::new(
-        app.assembly.display().to_string(),
-        fs::read(app.assembly).unwrap(),
-    );
-    let tokens = parser::parse(&unit).unwrap();
-    let assembly = compiler::compile(unit, tokens, &Limits::default()).unwrap();
+
+    match app.command {
+        Some(Command::Test {
+            assembly,
+            max_ticks,
+            allow,
+        }) => test(assembly, max_ticks, &allow),
+        None => run(app.run.expect("clap requires the run arguments")),
+    }
+}
+
+/// Assembles the file, printing its warnings to stderr.
+fn load(path: PathBuf, allow: &[Lint]) -> Assembly {
+    let data = fs::read(&path).unwrap_or_else(|err| {
+        fail(
+            &[Diagnostic::new(
+                format!("Can't read {}: {err}", path.display()),
+                None,
+            )],
+            &[],
+        )
+    });
+    let unit = SourceUnit::new(path.display().to_string(), data);
+    let (units, tokens) = parser::parse_with_includes(&unit, &FsResolver);
+    let tokens = tokens.unwrap_or_else(|errors| {
+        let diagnostics: Vec<Diagnostic> = errors.iter().map(Diagnostic::from).collect();
+
+        fail(&diagnostics, &units)
+    });
+
+    let mut lints = LintConfig::default();
+
+    for lint in allow {
+        lints.allow(*lint);
+    }
+
+    let (assembly, warnings) =
+        compiler::compile_with_lints(units.clone(), tokens, &Limits::default(), &lints)
+            .unwrap_or_else(|errors| {
+                let diagnostics: Vec<Diagnostic> = errors.iter().map(Diagnostic::from).collect();
+
+                fail(&diagnostics, &units)
+            });
+    let renderer = renderer(&io::stderr());
+
+    for warning in &warnings {
+        let diagnostic = Diagnostic::from(warning).with_expansions(expansions(&assembly));
+
+        eprint!("{}", renderer.render(&diagnostic, &units));
+    }
+
+    assembly
+}
+
+/// Picks colors when `stream` is a terminal and `NO_COLOR` isn't set.
+fn renderer(stream: &impl IsTerminal) -> Renderer {
+    if stream.is_terminal() && env::var_os("NO_COLOR").is_none() {
+        Renderer::colored()
+    } else {
+        Renderer::plain()
+    }
+}
+
+/// Prints the diagnostics to stderr and exits.
+fn fail(diagnostics: &[Diagnostic], units: &[SourceUnit]) -> ! {
+    let renderer = renderer(&io::stderr());
+
+    for diagnostic in diagnostics {
+        eprint!("{}", renderer.render(diagnostic, units));
+    }
+
+    process::exit(1)
+}
+
+/// Prints the stack issues of `assembly` and how deep its stack gets.
+fn check_stack(assembly: &Assembly) {
+    let report = stack::verify(assembly, &Limits::default());
+    let renderer = renderer(&io::stderr());
+
+    for issue in report.issues() {
+        eprint!(
+            "{}",
+            renderer.render(
+                &Diagnostic::from(issue).with_expansions(expansions(assembly)),
+                units(assembly)
+            )
+        );
+    }
+
+    match report.max_depth() {
+        Some(depth) => eprintln!("Maximum stack depth: {depth}"),
+        None => eprintln!("Maximum stack depth can't be determined statically"),
+    }
+}
+
+fn units(assembly: &Assembly) -> &[SourceUnit] {
+    assembly
+        .debug_info
+        .as_ref()
+        .map_or(&[], |info| info.units.as_slice())
+}
+
+fn expansions(assembly: &Assembly) -> &[Location] {
+    assembly
+        .debug_info
+        .as_ref()
+        .map_or(&[], |info| info.expansions.as_slice())
+}
+
+fn run(app: RunArgs) {
+    let assembly = load(app.assembly, &app.allow);
     let mut vm = VM::default();
+    let mut failed = false;
+
+    vm.set_host(Box::new(StdoutHost::new()));
+    vm.reseed();
+
+    if let Err(error) = vm.load_assembly(assembly.clone()) {
+        fail(
+            &[Diagnostic::from(&error).with_expansions(expansions(&assembly))],
+            units(&assembly),
+        );
+    }
+
+    if app.check_stack {
+        check_stack(&assembly);
+    }
+
+    if app.profile {
+        vm.enable_profiler();
+    }
+
+    if app.coverage.is_some() {
+        vm.enable_coverage();
+    }
 
-    vm.set_dbg_callback(Box::new(|message| println!("{message}")));
-    vm.load_assembly(assembly).unwrap();
+    let mut last_tick = Instant::now();
 
     loop {
         match vm.tick() {
             Ok(VMStatus::Finished | VMStatus::Idle) => break,
+            Ok(status @ (VMStatus::Yield | VMStatus::Sleeping)) => {
+                if status == VMStatus::Sleeping {
+                    thread::sleep(Duration::from_millis(1));
+                }
+
+                let now = Instant::now();
+
+                vm.advance_clock((now - last_tick).as_millis() as u64);
+                last_tick = now;
+            }
             Err(error) => {
-                eprintln!("Exception raised: {}", error.message());
-                return;
+                let location = assembly
+                    .debug_info
+                    .as_ref()
+                    .and_then(|info| info.source_loc.get(&vm.pc()).copied());
+                let diagnostic =
+                    Diagnostic::new(format!("Exception raised: {}", error.message()), location)
+                        .with_expansions(expansions(&assembly));
+
+                eprint!(
+                    "{}",
+                    renderer(&io::stderr()).render(&diagnostic, units(&assembly))
+                );
+                failed = true;
+                break;
             }
             _ => {}
         }
     }
+
+    if let (Some(profiler), Some(assembly)) = (vm.profiler(), vm.assembly()) {
+        print_profile(&profiler.report(assembly), assembly);
+    }
+
+    if let (Some(path), Some(coverage), Some(assembly)) =
+        (app.coverage, vm.coverage(), vm.assembly())
+    {
+        if let Err(err) = fs::write(&path, coverage.to_lcov(assembly)) {
+            fail(
+                &[Diagnostic::new(
+                    format!("Can't write {}: {err}", path.display()),
+                    None,
+                )],
+                &[],
+            );
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn test(path: PathBuf, max_ticks: u64, allow: &[Lint]) {
+    let assembly = load(path, allow);
+    let mut runner = TestRunner::new(assembly.clone(), Limits::default()).unwrap_or_else(|error| {
+        fail(
+            &[Diagnostic::from(&error).with_expansions(expansions(&assembly))],
+            units(&assembly),
+        )
+    });
+    let renderer = renderer(&io::stdout());
+
+    runner.set_max_ticks(max_ticks);
+
+    let results = runner.run_all();
+    let failed = results.iter().filter(|result| !result.passed()).count();
+
+    println!("running {} tests", results.len());
+
+    for result in &results {
+        match &result.outcome {
+            TestOutcome::Passed => println!("test {} ... ok", result.case.name),
+            TestOutcome::Failed { error, location } => {
+                println!("test {} ... FAILED", result.case.name);
+
+                let diagnostic = Diagnostic::new(error.message().to_string(), *location)
+                    .with_expansions(expansions(&assembly));
+
+                print!("{}", renderer.render(&diagnostic, units(&assembly)));
+            }
+            TestOutcome::TimedOut => {
+                println!("test {} ... TIMED OUT", result.case.name);
+                println!("  didn't finish in {max_ticks} instructions");
+            }
+        }
+
+        if !result.passed() {
+            for output in &result.output {
+                match output {
+                    DebugOutput::Number { value } => println!("  | {value}"),
+                    DebugOutput::String { value } => println!("  | {value}"),
+                }
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "test result: {}. {} passed; {failed} failed",
+        if failed == 0 { "ok" } else { "FAILED" },
+        results.len() - failed
+    );
+
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn print_profile(report: &ProfileReport, assembly: &Assembly) {
+    const TOP: usize = 10;
+
+    let percent = |hits: u64| hits as f64 * 100.0 / report.total.max(1) as f64;
+
+    println!();
+    println!("Executed {} instructions", report.total);
+    println!();
+    println!("{:>10} {:>7}  label", "hits", "%");
+
+    for label in &report.labels {
+        println!(
+            "{:>10} {:>6.2}%  {}",
+            label.hits,
+            percent(label.hits),
+            label.name.as_deref().unwrap_or("<entry>")
+        );
+    }
+
+    if !report.functions.is_empty() {
+        println!();
+        println!("{:>10} {:>10} {:>7}  function", "calls", "inclusive", "%");
+
+        for function in &report.functions {
+            println!(
+                "{:>10} {:>10} {:>6.2}%  {}",
+                function.calls,
+                function.inclusive,
+                percent(function.inclusive),
+                function
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| function.address.to_string())
+            );
+        }
+    }
+
+    println!();
+    println!("{:>10} {:>7} {:>6}  instruction", "hits", "%", "line");
+
+    for instruction in report.instructions.iter().take(TOP) {
+        let line = instruction
+            .location
+            .as_ref()
+            .map(|location| location.line.to_string())
+            .unwrap_or_default();
+        let text = assembly
+            .instructions
+            .get(instruction.address)
+            .map(|instruction| {
+                let mut text = instruction.operation.to_string();
+
+                for argument in &instruction.arguments {
+                    text.push_str(&format!(" {argument}"));
+                }
+
+                text
+            })
+            .unwrap_or_default();
+
+        println!(
+            "{:>10} {:>6.2}% {:>6}  {text}",
+            instruction.hits,
+            percent(instruction.hits),
+            line
+        );
+    }
 }
@@ -8,7 +8,10 @@ import {
   vm_get_status,
   vm_reset,
   vm_get_pc,
-  vm_get_sp
+  vm_get_sp,
+  vm_advance_clock,
+  vm_set_pin,
+  vm_get_pin
 } from '../../web/pkg'
 import { useAppStore } from './appStore'
 
@@ -17,27 +20,38 @@ export enum VMStatus {
   Yield,
   Running,
   Finished,
-  Error
+  Error,
+  Sleeping
 }
 
 export type Location = {
   line: number
   column: number
   offset: number
+  // Index of the included file, the main one is 0
+  unit?: number
+  // Index of the macro invocation this location was expanded from
+  expansion?: number
 }
 
 export type CodeError = {
   message: string
   location: Location
+  // Macro invocations the error was expanded from, innermost first
+  expanded_from?: Location[]
 }
 
 export type RuntimeError = {
   message: string
 }
 
+// Time spent ticking per animation frame, the rest is left to the browser
+const FRAME_BUDGET_MS = 8
+
 export default class NanoRiscVM {
   __handle: number
   __dbgCallback?: (text: string) => void
+  __frame?: number
 
   constructor() {
     this.__handle = vm_create()
@@ -56,13 +70,9 @@ export default class NanoRiscVM {
     this.reset()
 
     const store = useAppStore()
-    const error = vm_load_assembly(this.__handle, code) as CodeError | null
+    const errors = vm_load_assembly(this.__handle, code) as CodeError[] | null
 
-    if (error) {
-      store.vm.errors = [error]
-    } else {
-      store.vm.errors = []
-    }
+    store.vm.errors = errors ?? []
 
     this.__refreshData()
   }
@@ -71,14 +81,41 @@ export default class NanoRiscVM {
     return vm_pc_to_location(this.__handle)
   }
 
+  // Runs the program across animation frames so the page stays responsive.
+  // The clock advances once per frame, a frame ends early when the program
+  // yields or sleeps.
   run() {
-    let status = this.status()
     this.reset()
 
-    do {
-      this.tick()
-      status = this.status()
-    } while (status != VMStatus.Finished && status != VMStatus.Idle)
+    let lastFrame = performance.now()
+
+    const frame = (now: number) => {
+      vm_advance_clock(this.__handle, BigInt(Math.max(0, Math.floor(now - lastFrame))))
+      lastFrame = now
+
+      const deadline = performance.now() + FRAME_BUDGET_MS
+      let status: VMStatus
+      let error: RuntimeError | null = null
+
+      do {
+        error = vm_tick(this.__handle)
+        status = this.status()
+      } while (!error && status == VMStatus.Running && performance.now() < deadline)
+
+      this.__refreshData()
+
+      if (error) {
+        this.__reportError(error)
+      }
+
+      if (error || status == VMStatus.Finished || status == VMStatus.Idle) {
+        this.__frame = undefined
+      } else {
+        this.__frame = requestAnimationFrame(frame)
+      }
+    }
+
+    this.__frame = requestAnimationFrame(frame)
   }
 
   tick(): RuntimeError {
@@ -86,13 +123,26 @@ export default class NanoRiscVM {
     this.__refreshData()
 
     if (error) {
-      this.__dbgCallback?.call(undefined, `\x1b[31mRuntime error: ${error.message}\x1b[37m`)
+      this.__reportError(error)
     }
 
     return error
   }
 
+  setPin(id: number, value: number) {
+    vm_set_pin(this.__handle, id, value)
+  }
+
+  pin(id: number): number {
+    return vm_get_pin(this.__handle, id)
+  }
+
   reset() {
+    if (this.__frame !== undefined) {
+      cancelAnimationFrame(this.__frame)
+      this.__frame = undefined
+    }
+
     vm_reset(this.__handle)
     this.__refreshData()
   }
@@ -101,6 +151,10 @@ export default class NanoRiscVM {
     return vm_get_status(this.__handle)
   }
 
+  __reportError(error: RuntimeError) {
+    this.__dbgCallback?.call(undefined, `\x1b[31mRuntime error: ${error.message}\x1b[37m`)
+  }
+
   __refreshData() {
     const store = useAppStore()
 
@@ -0,0 +1,7 @@
+# Basic arithmetic on registers and immediates
+add $r0 2 3
+sub $r1 $r0 7
+mul $r2 $r0 $r1
+div $r3 7 2
+mod $r4 7 3
+dbg $r2
@@ -0,0 +1,4 @@
+# A failed assertion is a runtime error
+mov $r0 2
+asserteq $r0 2
+asserteq $r0 3 "r0 should be 3"
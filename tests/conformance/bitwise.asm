@@ -0,0 +1,8 @@
+# Bitwise operations work on integers, logical ones on truthiness
+andi $r0 12 10
+ori $r1 12 10
+xori $r2 12 10
+shl $r3 1 4
+shr $r4 256 2
+and $r5 12 0
+or $r6 12 0
@@ -0,0 +1,19 @@
+# Every conditional branch, taken and not taken
+mov $r0 0
+beq 1 1 a
+add $r0 $r0 100
+a:
+add $r0 $r0 1
+bne 1 1 b
+add $r0 $r0 10
+b:
+bgtz 1 c
+add $r0 $r0 100
+c:
+bltz 1 d
+add $r0 $r0 20
+d:
+slt $r1 1 2
+sge $r2 1 2
+seqz $r3 0
+dbg $r0
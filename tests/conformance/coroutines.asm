@@ -0,0 +1,11 @@
+# Coroutines share registers and interleave on switch
+spawn $r0 worker
+dbgs "main"
+join $r0
+dbgs "joined"
+halt
+
+worker:
+	dbgs "worker"
+	mov $r1 42
+	ret
@@ -0,0 +1,4 @@
+# Runtime errors stop the program
+mov $r0 1
+div $r1 $r0 0
+mov $r2 1
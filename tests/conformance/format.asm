@@ -0,0 +1,4 @@
+# Formatted debug output
+mov $r0 3.14159
+mov $r1 255
+dbgf "pi is {.2}, {x} in hex, {{literal}}" $r0 $r1
@@ -0,0 +1,7 @@
+# Loads and stores go through RAM
+sw 1000 300
+lw $r0 1000
+sb 1004 7
+lb $r1 1004
+sh 1008 513
+lh $r2 1008
@@ -0,0 +1,11 @@
+# Calls, returns and the stack
+mov $r0 3
+push 4
+call sum
+pop $r2
+halt
+
+sum:
+	mov $r1 7
+	add $r0 $r0 $r1
+	ret
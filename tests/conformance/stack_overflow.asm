@@ -0,0 +1,2 @@
+# Popping an empty stack fails
+pop $r0
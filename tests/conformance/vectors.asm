@@ -0,0 +1,7 @@
+# 3 component vector math
+mov $r0 1
+mov $r1 2
+mov $r2 2
+vlen3 $r10 $r0
+vscale3 $r3 $r0 2
+vdot3 $r11 $r0 $r3
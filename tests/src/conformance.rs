@@ -0,0 +1,130 @@
+//! Golden-output conformance suite.
+//!
+//! Every `.asm` file in `tests/conformance` is run until it stops and the
+//! result is compared against the `.expected` file next to it. The result
+//! consists of the debug output, the non-zero registers and how the program
+//! stopped, e.g.:
+//!
+//! ```text
+//! dbgs: Result:
+//! dbg: 120
+//! $r1 = 120
+//! finished
+//! ```
+//!
+//! Run the tests with `BLESS=1` to write the current results into the
+//! expectation files instead of comparing them.
+
+use nano_risc_arch::{Limits, SourceUnit};
+use nano_risc_asm::{compiler, parser};
+use nano_risc_vm::{CaptureHost, DebugOutput, VMStatus, VM};
+use std::{
+    env,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const MAX_TICKS: u64 = 100_000;
+
+fn conformance_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("conformance")
+}
+
+fn bless() -> bool {
+    env::var_os("BLESS").is_some_and(|value| value != "0")
+}
+
+/// Runs the program and renders its result in the expectation format.
+fn run(path: &Path) -> String {
+    let unit = SourceUnit::new(
+        path.file_name().unwrap().to_string_lossy().to_string(),
+        fs::read(path).unwrap(),
+    );
+    let tokens = parser::parse(&unit)
+        .unwrap_or_else(|errors| panic!("{}: {}", path.display(), errors[0].message()));
+    let assembly = compiler::compile(unit, tokens, &Limits::default())
+        .unwrap_or_else(|errors| panic!("{}: {}", path.display(), errors[0].message()));
+    let host = CaptureHost::new();
+    let mut vm = VM::default();
+
+    vm.set_host(Box::new(host.clone()));
+    vm.load_assembly(assembly)
+        .unwrap_or_else(|err| panic!("{}: {}", path.display(), err.message()));
+
+    let mut ticks = 0;
+    let outcome = loop {
+        if ticks == MAX_TICKS {
+            break format!("timed out after {MAX_TICKS} ticks");
+        }
+
+        ticks += 1;
+
+        match vm.tick() {
+            Ok(VMStatus::Finished | VMStatus::Idle) => break String::from("finished"),
+            Ok(VMStatus::Yield | VMStatus::Sleeping) => vm.advance_clock(1),
+            Ok(_) => {}
+            Err(err) => break format!("error {:?}: {}", err.kind(), err.message()),
+        }
+    };
+    let mut result = String::new();
+
+    for output in host.output() {
+        match output {
+            DebugOutput::Number { value } => writeln!(result, "dbg: {value}").unwrap(),
+            DebugOutput::String { value } => writeln!(result, "dbgs: {value}").unwrap(),
+        }
+    }
+
+    for (id, value) in vm.registers().iter().enumerate() {
+        if *value != 0.0 {
+            writeln!(result, "$r{id} = {value}").unwrap();
+        }
+    }
+
+    writeln!(result, "{outcome}").unwrap();
+
+    result
+}
+
+#[test]
+fn conformance() {
+    let mut programs: Vec<PathBuf> = fs::read_dir(conformance_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "asm"))
+        .collect();
+    let mut failures = Vec::new();
+
+    programs.sort();
+
+    assert!(!programs.is_empty(), "No conformance programs found");
+
+    for program in &programs {
+        let actual = run(program);
+        let expected_path = program.with_extension("expected");
+
+        if bless() {
+            fs::write(&expected_path, &actual).unwrap();
+            continue;
+        }
+
+        let expected = fs::read_to_string(&expected_path).unwrap_or_default();
+
+        if expected != actual {
+            failures.push(format!(
+                "{}\n--- expected\n{expected}--- actual\n{actual}",
+                program.display()
+            ));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} conformance programs differ from their expectations, \
+         rerun with BLESS=1 to accept the changes:\n\n{}",
+        failures.len(),
+        programs.len(),
+        failures.join("\n")
+    );
+}
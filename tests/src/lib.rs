@@ -1,8 +1,14 @@
+#[cfg(test)]
+mod conformance;
+
 #[cfg(test)]
 mod vm_tests {
-    use nano_risc_arch::{Limits, SourceUnit};
+    use nano_risc_arch::{Argument, Limits, SourceUnit};
     use nano_risc_asm::{compiler, parser};
-    use nano_risc_vm::{RuntimeErrorKind, VMStatus, VM};
+    use nano_risc_vm::{
+        BranchCoverage, CaptureHost, DebugOutput, RuntimeErrorKind, TestOutcome, TestRunner,
+        VMStatus, VM,
+    };
 
     fn create_vm_from(source: &str) -> VM {
         let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
@@ -682,168 +688,1580 @@ mod vm_tests {
     }
 
     #[test]
-    fn store() {
+    fn game_math() {
         let source = r#"
-            mov $r0 .data
-            mov $r1 .data
-            sb $r1 .data
+            sin $r0 0.5
+            cos $r1 0.5
+            tan $r2 0.5
+            asin $r3 0.5
+            acos $r4 0.5
+            atan2 $r5 1 -2
+            pow $r6 2 10
+            lerp $r7 10 20 0.25
+            clamp $r8 15 0 10
+            clamp $r9 -5 0 10
+            sign $r10 -3.5
+            sign $r11 0
+            select $r12 1 5 6
+            select $r13 0 5 6
+        "#;
+        let mut vm = create_vm_from(source);
 
-            add $r1 $r1 1
-            sh $r1 .data
+        while let VMStatus::Running = vm.tick().unwrap() {}
 
-            add $r1 $r1 2
-            sw $r1 .data
+        assert_eq!(vm.registers()[0], 0.5f32.sin());
+        assert_eq!(vm.registers()[1], 0.5f32.cos());
+        assert_eq!(vm.registers()[2], 0.5f32.tan());
+        assert_eq!(vm.registers()[3], 0.5f32.asin());
+        assert_eq!(vm.registers()[4], 0.5f32.acos());
+        assert_eq!(vm.registers()[5], f32::atan2(1.0, -2.0));
+        assert_eq!(vm.registers()[6], 1024.0);
+        assert_eq!(vm.registers()[7], 12.5);
+        assert_eq!(vm.registers()[8], 10.0);
+        assert_eq!(vm.registers()[9], 0.0);
+        assert_eq!(vm.registers()[10], -1.0);
+        assert_eq!(vm.registers()[11], 0.0);
+        assert_eq!(vm.registers()[12], 5.0);
+        assert_eq!(vm.registers()[13], 6.0);
+    }
+
+    #[test]
+    fn vectors() {
+        let source = r#"
+            mov $r0 1
+            mov $r1 2
+            mov $r2 3
+            mov $r3 4
+            mov $r4 5
+            mov $r5 6
+
+            vadd3 $r6 $r0 $r3
+            vsub2 $r9 $r3 $r0
+            vdot3 $r11 $r0 $r3
+            vcross $r12 $r0 $r3
         "#;
         let mut vm = create_vm_from(source);
 
         while let VMStatus::Running = vm.tick().unwrap() {}
 
-        let ram = vm.ram();
-        let data = vm.registers()[0] as usize;
+        assert_eq!(vm.registers()[6..9], [5.0, 7.0, 9.0]);
+        assert_eq!(vm.registers()[9..11], [3.0, 3.0]);
+        assert_eq!(vm.registers()[11], 32.0);
+        assert_eq!(vm.registers()[12..15], [-3.0, 6.0, -3.0]);
 
-        assert_eq!(data, 8);
-        assert_eq!(ram.read(0), Ok(8));
-        assert_eq!(ram.read(1), Ok(8));
-        assert_eq!(ram.read(2), Ok(0));
-        assert_eq!(ram.read(3), Ok(8));
-        assert_eq!(ram.read(4), Ok(0));
-        assert_eq!(ram.read(5), Ok(0));
-        assert_eq!(ram.read(6), Ok(0));
-        assert_eq!(ram.read(7), Ok(0));
+        let source = r#"
+            mov $r0 3
+            mov $r1 4
+            vlen2 $r2 $r0
+            vnorm2 $r3 $r0
+            vscale2 $r0 $r0 2
+            vnorm4 $r8 $r10
+        "#;
+        let mut vm = create_vm_from(source);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(vm.registers()[0..5], [6.0, 8.0, 5.0, 0.6, 0.8]);
+        assert_eq!(vm.registers()[8..12], [0.0, 0.0, 0.0, 0.0]);
     }
 
     #[test]
-    fn load() {
+    fn coroutines() {
         let source = r#"
-            mov $r9 .data
-            mov $r0 .data
+                spawn $r0 talk
+            walk:
+                dbg 1
+                switch $r0
+                add $r5 $r5 1
+                blt $r5 2 walk
+                join $r0
+                dbg 3
+                halt
+            talk:
+                push 7
+                dbg 2
+                switch 0
+                dbg 2
+                switch 0
+                pop $r6
+                dbg 4
+                ret
+        "#;
+        let mut vm = create_vm_from(source);
+        let host = CaptureHost::new();
 
-            sb $r0 16
-            lb $r1 $r0
+        vm.set_host(Box::new(host.clone()));
 
-            add $r0 $r0 1
-            sh $r0 32
-            lh $r2 $r0
+        while let VMStatus::Running = vm.tick().unwrap() {}
 
-            add $r0 $r0 2
-            sw $r0 64
-            lw $r3 $r0
+        assert_eq!(
+            host.output(),
+            [1.0, 2.0, 1.0, 2.0, 4.0, 3.0]
+                .map(|value| DebugOutput::Number { value })
+                .to_vec()
+        );
+        assert_eq!(vm.registers()[6], 7.0);
+        assert_eq!(vm.sp(), 0);
+        assert!(vm.coroutines().is_empty());
+    }
+
+    #[test]
+    fn coroutine_cancel() {
+        let source = r#"
+                spawn $r0 worker
+                cancel $r0
+                switch $r0
+            worker:
+                ret
+        "#;
+        let mut vm = create_vm_from(source);
+
+        assert_eq!(vm.tick(), Ok(VMStatus::Running));
+        assert_eq!(vm.tick(), Ok(VMStatus::Running));
+        assert_eq!(
+            vm.tick().map_err(|err| err.kind().clone()),
+            Err(RuntimeErrorKind::InvalidCoroutine { id: 1 })
+        );
+
+        let source = r#"
+                spawn $r0 worker
+                cancel $r0
+                cancel $r0
+                cancel 5
+            worker:
+                ret
+        "#;
+        let mut vm = create_vm_from(source);
+
+        assert_eq!(vm.tick(), Ok(VMStatus::Running));
+        assert_eq!(vm.tick(), Ok(VMStatus::Running));
+        assert_eq!(vm.tick(), Ok(VMStatus::Running));
+        assert_eq!(
+            vm.tick().map_err(|err| err.kind().clone()),
+            Err(RuntimeErrorKind::InvalidCoroutine { id: 5 })
+        );
+    }
+
+    #[test]
+    fn coroutine_blocked() {
+        let run = |source: &str| {
+            let mut vm = create_vm_from(source);
+
+            loop {
+                match vm.tick() {
+                    Ok(VMStatus::Running) => {}
+                    result => return result.map_err(|err| err.kind().clone()),
+                }
+            }
+        };
+
+        let switch_to_joining = r#"
+                spawn $r0 worker
+                join $r0
+                halt
+            worker:
+                switch 0
+                ret
+        "#;
+
+        assert_eq!(
+            run(switch_to_joining),
+            Err(RuntimeErrorKind::InvalidCoroutine { id: 0 })
+        );
+
+        let joined_by_each_other = r#"
+                spawn $r0 worker
+                join $r0
+                halt
+            worker:
+                join 0
+                ret
+        "#;
+
+        assert_eq!(run(joined_by_each_other), Err(RuntimeErrorKind::Deadlock));
+    }
+
+    #[test]
+    fn coroutine_sleep() {
+        let source = r#"
+                spawn $r0 worker
+                switch $r0
+                dbg 1
+                join $r0
+                dbg 3
+                halt
+            worker:
+                sleep 2
+                dbg 2
+                ret
         "#;
         let mut vm = create_vm_from(source);
+        let host = CaptureHost::new();
+
+        vm.set_host(Box::new(host.clone()));
 
         while let VMStatus::Running = vm.tick().unwrap() {}
 
-        assert_eq!(vm.registers()[1], 16.0);
-        assert_eq!(vm.registers()[2], 32.0);
-        assert_eq!(vm.registers()[3], 64.0);
+        assert_eq!(vm.status(), VMStatus::Sleeping);
+        assert_eq!(vm.coroutine(), 1);
+        assert_eq!(host.output(), vec![DebugOutput::Number { value: 1.0 }]);
+
+        vm.advance_clock(16);
+        vm.advance_clock(16);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(
+            host.output(),
+            [1.0, 2.0, 3.0]
+                .map(|value| DebugOutput::Number { value })
+                .to_vec()
+        );
     }
-}
 
-#[cfg(test)]
-mod compilation_tests {
-    use nano_risc_arch::{
-        Argument, Instruction, Limits, Operation, RegisterKind, RegisterMode, SourceUnit,
-    };
-    use nano_risc_asm::{
-        compiler::{self, CompilationErrorKind},
-        parser,
-    };
+    #[test]
+    fn sleep() {
+        let source = r#"
+            sleep 2
+            mov $r0 $clk
+            sleepms 100
+            mov $r1 $clk
+            wait p0
+            mov $r2 1
+        "#;
+        let mut vm = create_vm_from(source);
+        let host = CaptureHost::new();
+
+        vm.set_host(Box::new(host.clone()));
+
+        assert_eq!(vm.tick(), Ok(VMStatus::Sleeping));
+        assert_eq!(vm.tick(), Ok(VMStatus::Sleeping));
+        assert_eq!(vm.pc(), 1);
+
+        vm.advance_clock(16);
+        assert_eq!(vm.tick(), Ok(VMStatus::Sleeping));
+
+        vm.advance_clock(16);
+        assert_eq!(vm.tick(), Ok(VMStatus::Running));
+        assert_eq!(vm.registers()[0], 2.0);
+
+        assert_eq!(vm.tick(), Ok(VMStatus::Sleeping));
+        vm.advance_clock(50);
+        assert_eq!(vm.tick(), Ok(VMStatus::Sleeping));
+        vm.advance_clock(50);
+        assert_eq!(vm.tick(), Ok(VMStatus::Running));
+        assert_eq!(vm.registers()[1], 4.0);
+
+        assert_eq!(vm.tick(), Ok(VMStatus::Sleeping));
+        assert_eq!(vm.tick(), Ok(VMStatus::Sleeping));
+
+        host.set_pin(0, 1.0);
+        assert_eq!(vm.tick(), Ok(VMStatus::Running));
+        assert_eq!(vm.registers()[2], 1.0);
+        assert_eq!(vm.tick(), Ok(VMStatus::Finished));
+    }
 
     #[test]
-    fn duplicate_labels() {
+    fn profiler() {
         let source = r#"
-            start:
+                mov $r0 3
+            loop:
+                call work
+                sub $r0 $r0 1
+                bnez $r0 loop
                 halt
-            start:
-                jmp start
+            work:
+                add $r1 $r1 1
+                ret
         "#;
+        let mut vm = create_vm_from(source);
 
-        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
-        let tokens = parser::parse(&unit).unwrap();
-        let assembly = compiler::compile(unit, tokens, &Limits::default());
+        vm.enable_profiler();
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        let profiler = vm.profiler().unwrap();
+        let report = profiler.report(vm.assembly().unwrap());
 
+        assert_eq!(report.total, 17);
+        assert_eq!(profiler.hits(1), 3);
         assert_eq!(
-            assembly.map_err(|err| err.kind().clone()),
-            Err(CompilationErrorKind::DuplicateLabel {
-                name: String::from("start")
-            })
+            report
+                .labels
+                .iter()
+                .map(|label| (label.name.as_deref(), label.hits))
+                .collect::<Vec<_>>(),
+            vec![(Some("loop"), 10), (Some("work"), 6), (None, 1)]
         );
+        assert_eq!(report.functions.len(), 1);
+        assert_eq!(report.functions[0].name.as_deref(), Some("work"));
+        assert_eq!(report.functions[0].calls, 3);
+        assert_eq!(report.functions[0].inclusive, 6);
     }
 
     #[test]
-    fn constants() {
+    fn coverage() {
         let source = r#"
-            mov $r0 .data
-            mov $r1 .ram_end
+                mov $r0 2
+            loop:
+                sub $r0 $r0 1
+                bnez $r0 loop
+                beqz $r0 end
+                halt
+            end:
+                bltz $r0 loop
         "#;
+        let mut vm = create_vm_from(source);
 
-        let limits = Limits::default();
-        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
-        let tokens = parser::parse(&unit).unwrap();
-        let assembly = compiler::compile(unit, tokens, &limits).unwrap();
+        vm.enable_coverage();
 
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        let coverage = vm.coverage().unwrap();
+
+        assert_eq!(coverage.hits(1), 2);
+        assert_eq!(coverage.hits(4), 0);
         assert_eq!(
-            assembly.instructions,
-            vec![
-                Instruction {
-                    operation: Operation::Mov,
-                    arguments: vec![
-                        Argument::Register {
-                            register: RegisterKind::Regular {
-                                id: 0,
-                                mode: RegisterMode::Direct
-                            }
-                        },
-                        Argument::Int { value: 4 }
-                    ]
-                },
-                Instruction {
-                    operation: Operation::Mov,
-                    arguments: vec![
-                        Argument::Register {
-                            register: RegisterKind::Regular {
-                                id: 1,
-                                mode: RegisterMode::Direct
-                            }
-                        },
-                        Argument::Int {
-                            value: limits.ram_length as i32
-                        }
-                    ]
-                }
+            coverage.branch(2),
+            Some(BranchCoverage {
+                taken: 1,
+                not_taken: 1
+            })
+        );
+        assert_eq!(
+            coverage.branch(3),
+            Some(BranchCoverage {
+                taken: 1,
+                not_taken: 0
+            })
+        );
+        assert_eq!(
+            coverage.to_lcov(vm.assembly().unwrap()),
+            [
+                "TN:",
+                "SF:<anonymous>",
+                "BRDA:5,2,0,1",
+                "BRDA:5,2,1,1",
+                "BRDA:6,3,0,1",
+                "BRDA:6,3,1,0",
+                "BRDA:9,5,0,0",
+                "BRDA:9,5,1,1",
+                "BRF:6",
+                "BRH:4",
+                "DA:2,1",
+                "DA:4,2",
+                "DA:5,2",
+                "DA:6,1",
+                "DA:7,0",
+                "DA:9,1",
+                "LF:6",
+                "LH:5",
+                "end_of_record",
+                "",
             ]
-        )
+            .join("\n")
+        );
     }
 
     #[test]
-    fn max_size() {
+    fn assertions() {
         let source = r#"
-            mov $r1 1
-            mov $r1 1
-            mov $r1 1
-            mov $r1 1
-            mov $r1 1
-            mov $r1 1
-            mov $r1 1
-            mov $r1 1
-            mov $r1 1
-            mov $r1 1
+            mov $r0 2
+            assert $r0
+            asserteq $r0 2
+            assertne $r0 3 "r0 is not 3"
+            asserteq $r0 3 "r0 is 3"
         "#;
+        let mut vm = create_vm_from(source);
+
+        for _ in 0..4 {
+            assert_eq!(vm.tick(), Ok(VMStatus::Running));
+        }
 
+        let error = vm.tick().unwrap_err();
+
+        assert_eq!(error.kind(), &RuntimeErrorKind::AssertionFailed);
+        assert_eq!(
+            error.message(),
+            "Assertion failed: r0 is 3 (expected 3, found 2)"
+        );
+        assert_eq!(vm.pc(), 4);
+    }
+
+    #[test]
+    fn test_runner() {
+        let source = r#"
+                jmp end
+            double:
+                add $r0 $r0 $r0
+                ret
+            test_double:
+                mov $r0 4
+                call double
+                asserteq $r0 8
+                ret
+            test_fail:
+                dbgs "failing"
+                assert 0 "always fails"
+                ret
+            test_forever:
+                yield
+                jmp test_forever
+            end:
+        "#;
         let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
         let tokens = parser::parse(&unit).unwrap();
-        let assembly = compiler::compile(
-            unit,
-            tokens,
-            &Limits {
-                ram_length: 5,
-                ..Default::default()
-            },
+        let assembly = compiler::compile(unit, tokens, &Limits::default()).unwrap();
+        let mut runner = TestRunner::new(assembly, Limits::default()).unwrap();
+
+        runner.set_max_ticks(100);
+
+        let results = runner.run_all();
+        let names: Vec<&str> = results
+            .iter()
+            .map(|result| result.case.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["test_double", "test_fail", "test_forever"]);
+        assert_eq!(results[0].outcome, TestOutcome::Passed);
+        assert_eq!(
+            results[0].case.location.as_ref().map(|loc| loc.line),
+            Some(7)
         );
 
+        let TestOutcome::Failed { error, location } = &results[1].outcome else {
+            panic!("test_fail should fail");
+        };
+
+        assert_eq!(error.message(), "Assertion failed: always fails");
+        assert_eq!(location.as_ref().map(|loc| loc.line), Some(13));
         assert_eq!(
-            assembly.map_err(|err| err.kind().clone()),
-            Err(CompilationErrorKind::TooLargeAssembly { size: 5 })
-        )
+            results[1].output,
+            vec![DebugOutput::String {
+                value: String::from("failing")
+            }]
+        );
+        assert_eq!(results[2].outcome, TestOutcome::TimedOut);
+    }
+
+    #[test]
+    fn test_runner_local_labels() {
+        let source = r#"
+                jmp end
+            test_count:
+                mov $r0 3
+            @loop:
+                sub $r0 $r0 1
+                bnez $r0 @loop
+                ret
+            end:
+        "#;
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+        let assembly = compiler::compile(unit, tokens, &Limits::default()).unwrap();
+        let mut runner = TestRunner::new(assembly, Limits::default()).unwrap();
+
+        runner.set_max_ticks(100);
+
+        let results = runner.run_all();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].case.name, "test_count");
+        assert_eq!(results[0].outcome, TestOutcome::Passed);
+    }
+
+    #[test]
+    fn macros() {
+        let source = r#"
+            .macro twice_call target reg
+                push reg
+                call target
+                call target
+                pop reg
+            .endm
+
+            .macro countdown reg
+            loop:
+                sub reg reg 1
+                bgtz reg loop
+            .endm
+
+                mov $r1 5
+                twice_call double $r1
+                mov $r2 3
+                countdown $r2
+                mov $r3 2
+                countdown $r3
+                halt
+
+            double:
+                add $r0 $r0 2
+                ret
+        "#;
+        let mut vm = create_vm_from(source);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(vm.registers()[0], 4.0);
+        assert_eq!(vm.registers()[1], 5.0);
+        assert_eq!(vm.registers()[2], 0.0);
+        assert_eq!(vm.registers()[3], 0.0);
+
+        let labels = &vm.assembly().unwrap().debug_info.as_ref().unwrap().labels;
+
+        assert!(labels.contains_key("loop@2"));
+        assert!(labels.contains_key("loop@3"));
+    }
+
+    #[test]
+    fn constants() {
+        let source = r#"
+            .equ MAX_HP 100
+            .equ HALF 0.5
+            .equ START main
+            .equ LIMIT .MAX_HP
+            .equ GREETING "hi"
+
+            jmp .START
+        main:
+            mov $r0 .LIMIT
+            mul $r1 $r0 .HALF
+            dbgs .GREETING
+            halt
+        "#;
+        let mut vm = create_vm_from(source);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(vm.registers()[0], 100.0);
+        assert_eq!(vm.registers()[1], 50.0);
+
+        let constants = &vm
+            .assembly()
+            .unwrap()
+            .debug_info
+            .as_ref()
+            .unwrap()
+            .constants;
+
+        assert_eq!(constants.get("MAX_HP"), Some(&Argument::Int { value: 100 }));
+        assert_eq!(constants.get("LIMIT"), Some(&Argument::Int { value: 100 }));
+        assert_eq!(constants.get("HALF"), Some(&Argument::Float { value: 0.5 }));
+        assert_eq!(constants.get("START"), Some(&Argument::Int { value: 1 }));
+    }
+
+    #[test]
+    fn expressions() {
+        let source = r#"
+            .equ SIZE 4 * 8
+            .macro load reg offset
+                mov reg values + offset
+            .endm
+
+            mov $r0 .SIZE - 2
+            mov $r1 (1 + 2) * 3
+            mov $r2 -(1 << 4) | 0x3
+            mov $r3 .ram_end - 256
+            load $r4 1
+            mov $r5 17 % 5 ^ ~0
+            halt
+        values:
+            halt
+        "#;
+        let mut vm = create_vm_from(source);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(vm.registers()[0], 30.0);
+        assert_eq!(vm.registers()[1], 9.0);
+        assert_eq!(vm.registers()[2], -13.0);
+        assert_eq!(
+            vm.registers()[3],
+            (Limits::default().ram_length - 256) as f32
+        );
+        assert_eq!(vm.registers()[4], 8.0);
+        assert_eq!(vm.registers()[5], -3.0);
+    }
+
+    #[test]
+    fn data_directives() {
+        let source = r#"
+                lw $r0 squares + 12
+                lb $r1 bytes + 2
+                sw buffer + 4 77
+                lw $r2 buffer + 4
+                lw $r3 pointers + 4
+                lb $r3 $r3
+                lb $r4 greeting
+                dbgs greeting
+                halt
+
+            squares:
+                .word 0 1 4 9 16
+            bytes:
+                .byte 1 2 -1
+            buffer:
+                .space 8
+            greeting:
+                .string "hi"
+            one:
+                .float 1.0
+            pointers:
+                .word "a" "b"
+        "#;
+        let mut vm = create_vm_from(source);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(vm.registers()[0], 9.0);
+        assert_eq!(vm.registers()[1], 255.0);
+        assert_eq!(vm.registers()[2], 77.0);
+        assert_eq!(vm.registers()[3], b'b' as f32);
+        assert_eq!(vm.registers()[4], b'h' as f32);
+
+        let assembly = vm.assembly().unwrap();
+        let data = &assembly.text_section;
+
+        assert_eq!(
+            &data[..20],
+            &[0, 0, 0, 0, 1, 0, 0, 0, 4, 0, 0, 0, 9, 0, 0, 0, 16, 0, 0, 0]
+        );
+        assert_eq!(&data[20..23], &[1, 2, 255]);
+        assert_eq!(&data[31..34], b"hi\0");
+        assert_eq!(&data[34..38], &1.0f32.to_le_bytes());
+        assert_eq!(
+            &data[38..46],
+            &[
+                (assembly.code_section_size + 46) as u8,
+                0,
+                0,
+                0,
+                (assembly.code_section_size + 48) as u8,
+                0,
+                0,
+                0
+            ]
+        );
+        assert_eq!(&data[46..], b"a\0b\0");
+        assert!(!assembly
+            .debug_info
+            .as_ref()
+            .unwrap()
+            .labels
+            .contains_key("squares"));
+    }
+
+    #[test]
+    fn local_and_anonymous_labels() {
+        let source = r#"
+                mov $r0 4
+                call fact
+                mov $r2 3
+                call count
+                halt
+            fact:
+                mov $r1 1
+            @loop:
+                blez $r0 @done
+                mul $r1 $r1 $r0
+                dec $r0
+                jmp @loop
+            @done:
+                ret
+            count:
+                mov $r3 0
+            1:
+                beqz $r2 1f
+                inc $r3
+                dec $r2
+                jmp 1b
+            1:
+                ret
+        "#;
+        let mut vm = create_vm_from(source);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(vm.registers()[1], 24.0);
+        assert_eq!(vm.registers()[3], 3.0);
+
+        let labels = &vm.assembly().unwrap().debug_info.as_ref().unwrap().labels;
+
+        assert_eq!(
+            labels.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["1#1", "1#2", "count", "fact", "fact@done", "fact@loop"]
+        );
+    }
+
+    #[test]
+    fn literals() {
+        let source = r#"
+                mov $r0 'a'
+                mov $r1 1.5e1
+                mov $r2 'a' + 1
+                mov $r3 .5
+                lb $r4 quoted + 1
+                halt
+            quoted:
+                .string "\"\x41\n"
+        "#;
+        let mut vm = create_vm_from(source);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(&vm.registers()[..5], &[97.0, 15.0, 98.0, 0.5, 65.0]);
+        assert_eq!(&vm.assembly().unwrap().text_section[..4], b"\"A\n\0");
+    }
+
+    #[test]
+    fn comments() {
+        let source = r##"
+                #no space
+                .equ STEP 2 # trailing
+                mov $r0 1 # explain
+                /* a block
+                   over lines */ call twice
+                lb $r2 text # after data
+                halt
+            ## Doubles $r0 into $r1.
+            twice: # the label
+                mul $r1 $r0 .STEP /* inline */
+                ret
+            text:
+                .string "#" /* after a string */
+        "##;
+        let mut vm = create_vm_from(source);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(&vm.registers()[..3], &[1.0, 2.0, 35.0]);
+    }
+
+    #[test]
+    fn pseudo_instructions() {
+        let source = r#"
+                mov $r0 5
+                mov $r1 9
+                inc $r0
+                dec $r1
+                swap $r0 $r1
+                mov $r2 3
+                neg $r2
+                mov $r3 12
+                not $r3
+                mov $r4 9
+                clr $r4
+                nop
+                bl double
+                b end
+                mov $r6 1
+            end:
+                halt
+            double:
+                add $r5 $r0 $r0
+                ret
+        "#;
+        let mut vm = create_vm_from(source);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(
+            &vm.registers()[..7],
+            &[8.0, 6.0, -3.0, -13.0, 0.0, 16.0, 0.0]
+        );
+
+        let source_loc = &vm
+            .assembly()
+            .unwrap()
+            .debug_info
+            .as_ref()
+            .unwrap()
+            .source_loc;
+        let lines: Vec<usize> = source_loc.values().map(|location| location.line).collect();
+
+        assert_eq!(
+            lines,
+            vec![2, 3, 4, 5, 6, 6, 6, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 18, 20, 21]
+        );
+    }
+
+    #[test]
+    fn random() {
+        let source = r#"
+            rand $r0 -1 1
+            randi $r1 10 20
+            rand $r2 0 100
+        "#;
+        let mut a = create_vm_from(source);
+        let mut b = create_vm_from(source);
+
+        a.set_seed(1234);
+        b.set_seed(1234);
+
+        while let VMStatus::Running = a.tick().unwrap() {}
+        while let VMStatus::Running = b.tick().unwrap() {}
+
+        assert_eq!(a.registers(), b.registers());
+        assert_eq!(a.rng(), b.rng());
+        assert!((-1.0..1.0).contains(&a.registers()[0]));
+        assert!((10.0..=20.0).contains(&a.registers()[1]));
+        assert_eq!(a.registers()[1].fract(), 0.0);
+
+        let registers = a.registers().to_vec();
+
+        a.reset();
+        while let VMStatus::Running = a.tick().unwrap() {}
+
+        assert_eq!(a.registers(), registers);
+    }
+
+    #[test]
+    fn store() {
+        let source = r#"
+            mov $r0 .data
+            mov $r1 .data
+            sb $r1 .data
+
+            add $r1 $r1 1
+            sh $r1 .data
+
+            add $r1 $r1 2
+            sw $r1 .data
+        "#;
+        let mut vm = create_vm_from(source);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        let ram = vm.ram();
+        let data = vm.registers()[0] as usize;
+
+        assert_eq!(data, 8);
+        assert_eq!(ram.read(0), Ok(8));
+        assert_eq!(ram.read(1), Ok(8));
+        assert_eq!(ram.read(2), Ok(0));
+        assert_eq!(ram.read(3), Ok(8));
+        assert_eq!(ram.read(4), Ok(0));
+        assert_eq!(ram.read(5), Ok(0));
+        assert_eq!(ram.read(6), Ok(0));
+        assert_eq!(ram.read(7), Ok(0));
+    }
+
+    #[test]
+    fn load() {
+        let source = r#"
+            mov $r9 .data
+            mov $r0 .data
+
+            sb $r0 16
+            lb $r1 $r0
+
+            add $r0 $r0 1
+            sh $r0 32
+            lh $r2 $r0
+
+            add $r0 $r0 2
+            sw $r0 64
+            lw $r3 $r0
+        "#;
+        let mut vm = create_vm_from(source);
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(vm.registers()[1], 16.0);
+        assert_eq!(vm.registers()[2], 32.0);
+        assert_eq!(vm.registers()[3], 64.0);
+    }
+
+    #[test]
+    fn dbg() {
+        let source = r#"
+            dbg 5
+            dbgs "Hello"
+            dbg 1.5
+        "#;
+        let mut vm = create_vm_from(source);
+        let host = CaptureHost::new();
+
+        vm.set_host(Box::new(host.clone()));
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(
+            host.output(),
+            vec![
+                DebugOutput::Number { value: 5.0 },
+                DebugOutput::String {
+                    value: String::from("Hello")
+                },
+                DebugOutput::Number { value: 1.5 },
+            ]
+        );
+    }
+
+    #[test]
+    fn dbgf() {
+        let source = r#"
+            mov $r0 3
+            mov $r1 4.25
+            dbgf "x = {}, y = {.1}, {i} {x} {{}}" $r0 $r1 $r1 255
+        "#;
+        let mut vm = create_vm_from(source);
+        let host = CaptureHost::new();
+
+        vm.set_host(Box::new(host.clone()));
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(
+            host.output(),
+            vec![DebugOutput::String {
+                value: String::from("x = 3, y = 4.2, 4 0xff {}")
+            }]
+        );
+    }
+
+    #[test]
+    fn pins() {
+        let source = r#"
+            mov $r0 p0
+            mov p1 $r0
+            mov p2 7
+        "#;
+        let mut vm = create_vm_from(source);
+        let host = CaptureHost::new();
+
+        host.set_pin(0, 42.0);
+        vm.set_host(Box::new(host.clone()));
+
+        while let VMStatus::Running = vm.tick().unwrap() {}
+
+        assert_eq!(vm.registers()[0], 42.0);
+        assert_eq!(host.pin(1), 42.0);
+        assert_eq!(host.pin(2), 7.0);
+    }
+}
+
+#[cfg(test)]
+mod compilation_tests {
+    use nano_risc_arch::{
+        Argument, Assembly, AssemblyErrorKind, Instruction, Limits, Location, Operation,
+        RegisterKind, RegisterMode, SourceUnit,
+    };
+    use nano_risc_asm::{
+        compiler::{self, CompilationErrorKind},
+        lints::{Lint, LintConfig},
+        parser::{self, MemoryResolver},
+    };
+
+    fn compile_errors(source: &str) -> Result<Assembly, Vec<CompilationErrorKind>> {
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+
+        compiler::compile(unit, tokens, &Limits::default())
+            .map_err(|errors| errors.iter().map(|error| error.kind().clone()).collect())
+    }
+
+    #[test]
+    fn duplicate_labels() {
+        let source = r#"
+            start:
+                halt
+            start:
+                jmp start
+        "#;
+
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+        let assembly = compiler::compile(unit, tokens, &Limits::default());
+
+        assert_eq!(
+            assembly.map_err(|errors| errors[0].kind().clone()),
+            Err(CompilationErrorKind::DuplicateLabel {
+                name: String::from("start")
+            })
+        );
+    }
+
+    #[test]
+    fn constants() {
+        let source = r#"
+            mov $r0 .data
+            mov $r1 .ram_end
+        "#;
+
+        let limits = Limits::default();
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+        let assembly = compiler::compile(unit, tokens, &limits).unwrap();
+
+        assert_eq!(
+            assembly.instructions,
+            vec![
+                Instruction {
+                    operation: Operation::Mov,
+                    arguments: vec![
+                        Argument::Register {
+                            register: RegisterKind::Regular {
+                                id: 0,
+                                mode: RegisterMode::Direct
+                            }
+                        },
+                        Argument::Int { value: 4 }
+                    ]
+                },
+                Instruction {
+                    operation: Operation::Mov,
+                    arguments: vec![
+                        Argument::Register {
+                            register: RegisterKind::Regular {
+                                id: 1,
+                                mode: RegisterMode::Direct
+                            }
+                        },
+                        Argument::Int {
+                            value: limits.ram_length as i32
+                        }
+                    ]
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn dbgf_arguments_count() {
+        let source = r#"
+            dbgf "{} {}" 1
+        "#;
+
+        assert_eq!(
+            compile_errors(source),
+            Err(vec![CompilationErrorKind::FormatArgumentsMismatch {
+                expected: 2,
+                found: 1
+            }])
+        );
+    }
+
+    #[test]
+    fn dbgf_format_through_constants() {
+        assert_eq!(
+            compile_errors(".equ FORMAT \"{} {}\"\n.equ ALIAS .FORMAT\ndbgf .ALIAS 1"),
+            Err(vec![CompilationErrorKind::FormatArgumentsMismatch {
+                expected: 2,
+                found: 1
+            }])
+        );
+        assert_eq!(
+            compile_errors("dbgf $r0 1"),
+            Err(vec![CompilationErrorKind::InvalidFormat])
+        );
+        assert!(compile_errors(".equ FORMAT \"{}\"\ndbgf .FORMAT 1").is_ok());
+    }
+
+    #[test]
+    fn dbgf_format_location() {
+        let mut resolver = MemoryResolver::new();
+
+        resolver.add_file("lib.asm", br#"dbgf "\t{bad}" 1"#.to_vec());
+
+        let unit = SourceUnit::new_anonymous(b"halt\n.include \"lib.asm\"".to_vec());
+        let (units, tokens) = parser::parse_with_includes(&unit, &resolver);
+        let errors =
+            compiler::compile_units(units, tokens.unwrap(), &Limits::default()).unwrap_err();
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| (error.kind().clone(), error.location()))
+                .collect::<Vec<_>>(),
+            vec![(
+                CompilationErrorKind::InvalidFormat,
+                Location {
+                    line: 1,
+                    column: 9,
+                    offset: 8,
+                    unit: 1,
+                    expansion: None,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn game_math_arity() {
+        let source = r#"
+            lerp $r0 1 2
+        "#;
+
+        assert_eq!(
+            compile_errors(source),
+            Err(vec![CompilationErrorKind::InvalidInstruction {
+                kind: AssemblyErrorKind::InvalidInstruction {
+                    name: String::from("lerp")
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn vector_out_of_bounds() {
+        let source = r#"
+            vadd4 $r0 $r4 $r14
+        "#;
+
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+        let errors = compiler::compile(unit, tokens, &Limits::default()).unwrap_err();
+
+        assert_eq!(
+            (
+                errors[0].location().line,
+                errors[0].location().column,
+                errors[0].kind().clone()
+            ),
+            (
+                2,
+                27,
+                CompilationErrorKind::InvalidInstruction {
+                    kind: AssemblyErrorKind::InvalidRegisterRange { id: 14, size: 4 }
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn instruction_validation() {
+        let errors = |source: &str| {
+            let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+            let tokens = parser::parse(&unit).unwrap();
+
+            compiler::compile(unit, tokens, &Limits::default())
+                .unwrap_err()
+                .iter()
+                .map(|err| (err.location().column, err.message().to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            errors("jmp 1.5"),
+            vec![(
+                5,
+                String::from("jmp's target accepts only numbers and registers")
+            )]
+        );
+        assert_eq!(
+            errors("mov 1 2\nbeqz $r0 2.5\nadd $r0 1"),
+            vec![
+                (
+                    5,
+                    String::from("mov's first argument accepts only registers and pins")
+                ),
+                (
+                    10,
+                    String::from("beqz's target accepts only numbers and registers")
+                ),
+                (1, String::from("add requires 3 arguments")),
+            ]
+        );
+        assert_eq!(
+            errors("mov $r16 1\ndbg p9"),
+            vec![
+                (
+                    5,
+                    String::from("Register's id 16 is out of bounds (maximum: 15)")
+                ),
+                (5, String::from("Pin's id 9 is out of bounds (maximum: 7)")),
+            ]
+        );
+    }
+
+    #[test]
+    fn load_time_validation() {
+        let source = r#"
+            push $r8
+        "#;
+
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+        let assembly = compiler::compile(unit, tokens, &Limits::default()).unwrap();
+        let error = assembly
+            .validate(&Limits {
+                regular_registers: 8,
+                ..Limits::default()
+            })
+            .unwrap_err();
+
+        assert_eq!(error.kind(), &AssemblyErrorKind::InvalidRegister { id: 8 });
+        assert_eq!(error.location().map(|location| location.line), Some(2));
+    }
+
+    #[test]
+    fn max_size() {
+        let source = r#"
+            mov $r1 1
+            mov $r1 1
+            mov $r1 1
+            mov $r1 1
+            mov $r1 1
+            mov $r1 1
+            mov $r1 1
+            mov $r1 1
+            mov $r1 1
+            mov $r1 1
+        "#;
+
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+        let assembly = compiler::compile(
+            unit,
+            tokens,
+            &Limits {
+                ram_length: 5,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            assembly.map_err(|errors| errors[0].kind().clone()),
+            Err(CompilationErrorKind::TooLargeAssembly { size: 5 })
+        )
+    }
+
+    #[test]
+    fn macro_error_location() {
+        let source = r#"
+            .macro store value
+                mov $r0 value
+                bogus value
+            .endm
+
+            store 1
+        "#;
+
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+        let error = compiler::compile(unit, tokens, &Limits::default())
+            .unwrap_err()
+            .remove(0);
+        let location = error.location();
+
+        assert_eq!(error.kind(), &CompilationErrorKind::InvalidOperation);
+        assert_eq!((location.line, location.column), (4, 17));
+        assert_eq!(
+            error
+                .expanded_from()
+                .iter()
+                .map(|invocation| (invocation.line, invocation.column))
+                .collect::<Vec<_>>(),
+            [(7, 13)]
+        );
+    }
+
+    #[test]
+    fn constant_errors() {
+        assert_eq!(
+            compile_errors(".equ A 1\n.equ A 2"),
+            Err(vec![CompilationErrorKind::DuplicateConstant {
+                name: String::from("A")
+            }])
+        );
+        assert_eq!(
+            compile_errors(".equ data 1"),
+            Err(vec![CompilationErrorKind::InvalidConstant {
+                name: String::from("data")
+            }])
+        );
+        assert_eq!(
+            compile_errors(".equ A 1 2"),
+            Err(vec![CompilationErrorKind::InvalidConstant {
+                name: String::from("A")
+            }])
+        );
+        assert_eq!(
+            compile_errors(".equ X 1 2\nstart:\nhalt\nstart:\nhalt"),
+            Err(vec![
+                CompilationErrorKind::InvalidConstant {
+                    name: String::from("X")
+                },
+                CompilationErrorKind::DuplicateLabel {
+                    name: String::from("start")
+                },
+            ])
+        );
+        assert_eq!(
+            compile_errors(".equ A .B\n.equ B .A"),
+            Err(vec![
+                CompilationErrorKind::InvalidConstant {
+                    name: String::from("B")
+                },
+                CompilationErrorKind::InvalidConstant {
+                    name: String::from("A")
+                },
+            ])
+        );
+        assert_eq!(
+            compile_errors(".equ A $r0"),
+            Err(vec![CompilationErrorKind::InvalidConstant {
+                name: String::from("A")
+            }])
+        );
+        assert_eq!(
+            compile_errors("mov $r0 .MISSING"),
+            Err(vec![CompilationErrorKind::UnknownConstant {
+                name: String::from("MISSING")
+            }])
+        );
+    }
+
+    #[test]
+    fn expression_errors() {
+        assert_eq!(
+            compile_errors("mov $r0 2147483647 + 1"),
+            Err(vec![CompilationErrorKind::ExpressionOverflow])
+        );
+        assert_eq!(
+            compile_errors("mov $r0 1 << 32"),
+            Err(vec![CompilationErrorKind::ExpressionOverflow])
+        );
+        assert_eq!(
+            compile_errors("mov $r0 1 / (1 - 1)"),
+            Err(vec![CompilationErrorKind::InvalidExpression])
+        );
+        assert_eq!(
+            compile_errors(".equ HALF 0.5\nmov $r0 .HALF * 2"),
+            Err(vec![CompilationErrorKind::InvalidExpression])
+        );
+        assert_eq!(
+            compile_errors("mov $r0 missing + 1"),
+            Err(vec![CompilationErrorKind::UnknownLabel {
+                name: String::from("missing")
+            }])
+        );
+        assert_eq!(
+            compile_errors("mov $r0 .MISSING * 2"),
+            Err(vec![CompilationErrorKind::UnknownConstant {
+                name: String::from("MISSING")
+            }])
+        );
+    }
+
+    #[test]
+    fn data_errors() {
+        assert_eq!(
+            compile_errors(".byte 256"),
+            Err(vec![CompilationErrorKind::InvalidData])
+        );
+        assert_eq!(
+            compile_errors(".word 1.5"),
+            Err(vec![CompilationErrorKind::InvalidData])
+        );
+        assert_eq!(
+            compile_errors(".space -1"),
+            Err(vec![CompilationErrorKind::InvalidData])
+        );
+        assert_eq!(
+            compile_errors(".space 1 2"),
+            Err(vec![CompilationErrorKind::InvalidData])
+        );
+        assert_eq!(
+            compile_errors(".string 5"),
+            Err(vec![CompilationErrorKind::InvalidData])
+        );
+        assert_eq!(
+            compile_errors("a:\n.byte 1\na:\nhalt"),
+            Err(vec![CompilationErrorKind::DuplicateLabel {
+                name: String::from("a")
+            }])
+        );
+    }
+
+    #[test]
+    fn multiple_errors() {
+        let source = r#"
+            mov $r0 missing
+            bogus 1
+            .equ A .B
+            mov $r1 .A
+            add $r2 1 2
+            jmp missing
+        "#;
+
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+        let errors = compiler::compile(unit, tokens, &Limits::default())
+            .unwrap_err()
+            .iter()
+            .map(|err| (err.location().line, err.kind().clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    2,
+                    CompilationErrorKind::UnknownLabel {
+                        name: String::from("missing")
+                    }
+                ),
+                (3, CompilationErrorKind::InvalidOperation),
+                (
+                    4,
+                    CompilationErrorKind::UnknownConstant {
+                        name: String::from("B")
+                    }
+                ),
+                (
+                    7,
+                    CompilationErrorKind::UnknownLabel {
+                        name: String::from("missing")
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn literal_errors() {
+        let source = "mov $r0 1\nmov $r1 99999999999\ndbgs \"\\q\"\nmov $r2 'ab'";
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let errors = parser::parse(&unit)
+            .unwrap_err()
+            .iter()
+            .map(|err| {
+                (
+                    err.location().line,
+                    err.location().column,
+                    err.message().to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    2,
+                    9,
+                    String::from("Integer literal 99999999999 doesn't fit in 32 bits")
+                ),
+                (3, 7, String::from("Unknown escape sequence: \\q")),
+                (
+                    4,
+                    9,
+                    String::from("Character literals hold a single character")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn scoped_duplicate_labels() {
+        assert!(compile_errors("a:\n@loop:\nhalt\nb:\n@loop:\njmp @loop").is_ok());
+        assert_eq!(
+            compile_errors("a:\n@loop:\nhalt\n@loop:\njmp @loop"),
+            Err(vec![CompilationErrorKind::DuplicateLabel {
+                name: String::from("a@loop")
+            }])
+        );
+        assert_eq!(
+            compile_errors("a:\nhalt\nb:\njmp @missing"),
+            Err(vec![CompilationErrorKind::UnknownLabel {
+                name: String::from("b@missing")
+            }])
+        );
+        assert_eq!(
+            compile_errors("jmp 1f"),
+            Err(vec![CompilationErrorKind::UnknownLabel {
+                name: String::from("1f")
+            }])
+        );
+        assert!(compile_errors(
+            ".macro helper\ninner:\n@skip:\njmp @skip\n.endm\nmain:\n@loop:\nhelper\nbnez $r0 @loop"
+        )
+        .is_ok());
+    }
+
+    fn lint(source: &str, config: &LintConfig) -> Vec<(usize, Lint)> {
+        let unit = SourceUnit::new_anonymous(source.as_bytes().to_vec());
+        let tokens = parser::parse(&unit).unwrap();
+        let (_, warnings) =
+            compiler::compile_with_lints(vec![unit], tokens, &Limits::default(), config).unwrap();
+
+        warnings
+            .iter()
+            .map(|warning| (warning.location().line, warning.lint()))
+            .collect()
+    }
+
+    #[test]
+    fn lints() {
+        let source = r#"
+            start:
+                mov $r0 %r3
+                add $pc $pc 1
+                beq $r0 0 next
+            next:
+                dbgs 5
+                dbgs "ok"
+                jmp start
+                halt
+            unused:
+                ret
+            test_entry:
+                ret
+        "#;
+
+        assert_eq!(
+            lint(source, &LintConfig::default()),
+            vec![
+                (3, Lint::UninitializedIndirect),
+                (4, Lint::ArithmeticPcWrite),
+                (5, Lint::BranchToNext),
+                (7, Lint::NonStringDbgs),
+                (10, Lint::UnreachableCode),
+                (11, Lint::UnusedLabel),
+            ]
+        );
+
+        let mut config = LintConfig::default();
+        config.allow(Lint::UnusedLabel);
+        config.allow(Lint::BranchToNext);
+
+        assert_eq!(
+            lint(source, &config),
+            vec![
+                (3, Lint::UninitializedIndirect),
+                (4, Lint::ArithmeticPcWrite),
+                (7, Lint::NonStringDbgs),
+                (10, Lint::UnreachableCode),
+            ]
+        );
+        assert_eq!(lint(source, &LintConfig::none()), vec![]);
+    }
+
+    #[test]
+    fn lints_resolve_operands() {
+        let source = r#"
+            .equ GREETING "hi"
+            .equ COUNT 100
+            dbgs .GREETING
+            dbgs .COUNT
+            and $pc $pc 1
+            pop $pc
+        "#;
+
+        assert_eq!(
+            lint(source, &LintConfig::default()),
+            vec![(5, Lint::NonStringDbgs), (6, Lint::ArithmeticPcWrite)]
+        );
+    }
+
+    #[test]
+    fn lint_names() {
+        for lint in Lint::ALL {
+            assert_eq!(lint.name().parse::<Lint>(), Ok(lint));
+        }
+
+        assert!("bogus".parse::<Lint>().is_err());
     }
 }
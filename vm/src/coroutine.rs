@@ -0,0 +1,48 @@
+use crate::Sleep;
+
+/// A suspended execution context.
+///
+/// Coroutines share registers and RAM, but each one has its own program
+/// counter, stack and sleep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coroutine {
+    pub(crate) pc: usize,
+    pub(crate) sp: usize,
+    pub(crate) stack: Vec<f32>,
+    pub(crate) waiting_for: Option<usize>,
+    pub(crate) sleep: Option<Sleep>,
+}
+
+impl Coroutine {
+    pub(crate) fn new(pc: usize, stack_size: usize) -> Self {
+        Self {
+            pc,
+            sp: 0,
+            stack: vec![0.0; stack_size],
+            waiting_for: None,
+            sleep: None,
+        }
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn sp(&self) -> usize {
+        self.sp
+    }
+
+    pub fn stack(&self) -> &[f32] {
+        &self.stack
+    }
+
+    /// The coroutine that this one is joining.
+    pub fn waiting_for(&self) -> Option<usize> {
+        self.waiting_for
+    }
+
+    /// Why the coroutine was suspended while sleeping.
+    pub fn sleep(&self) -> Option<Sleep> {
+        self.sleep
+    }
+}
@@ -0,0 +1,126 @@
+use nano_risc_arch::Assembly;
+use std::{collections::BTreeMap, fmt::Write};
+
+/// Records which instructions ran and which way every conditional branch
+/// went.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Coverage {
+    hits: BTreeMap<usize, u64>,
+    branches: BTreeMap<usize, BranchCoverage>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BranchCoverage {
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default()
+    }
+
+    pub fn hits(&self, address: usize) -> u64 {
+        self.hits.get(&address).copied().unwrap_or_default()
+    }
+
+    pub fn branch(&self, address: usize) -> Option<BranchCoverage> {
+        self.branches.get(&address).copied()
+    }
+
+    pub(crate) fn record(&mut self, address: usize, branch_taken: Option<bool>) {
+        *self.hits.entry(address).or_default() += 1;
+
+        if let Some(taken) = branch_taken {
+            let branch = self.branches.entry(address).or_default();
+
+            if taken {
+                branch.taken += 1;
+            } else {
+                branch.not_taken += 1;
+            }
+        }
+    }
+
+    /// Renders the coverage in the lcov tracefile format, one record per
+    /// file.
+    ///
+    /// Lines come from the assembly's debug info, so an assembly without it
+    /// produces an empty report.
+    pub fn to_lcov(&self, assembly: &Assembly) -> String {
+        let mut lcov = String::new();
+        let Some(debug_info) = assembly.debug_info.as_ref() else {
+            return lcov;
+        };
+
+        for (index, unit) in debug_info.units.iter().enumerate() {
+            let mut lines = BTreeMap::<usize, u64>::new();
+            let mut branches = Vec::new();
+
+            for (address, instruction) in assembly.instructions.iter().enumerate() {
+                let Some(location) = debug_info.source_loc.get(&address) else {
+                    continue;
+                };
+
+                if location.unit != index {
+                    continue;
+                }
+
+                let hits = self.hits(address);
+                let line = lines.entry(location.line).or_default();
+
+                *line = (*line).max(hits);
+
+                if instruction.operation.is_conditional_branch() {
+                    branches.push((location.line, address, hits, self.branch(address)));
+                }
+            }
+
+            if lines.is_empty() {
+                continue;
+            }
+
+            let _ = writeln!(lcov, "TN:");
+            let _ = writeln!(lcov, "SF:{}", unit.name());
+
+            let mut branches_hit = 0;
+
+            for (line, address, hits, branch) in &branches {
+                let branch = branch.unwrap_or_default();
+
+                for (id, count) in [branch.taken, branch.not_taken].iter().enumerate() {
+                    if *hits == 0 {
+                        let _ = writeln!(lcov, "BRDA:{line},{address},{id},-");
+                    } else {
+                        let _ = writeln!(lcov, "BRDA:{line},{address},{id},{count}");
+                    }
+
+                    if *count > 0 {
+                        branches_hit += 1;
+                    }
+                }
+            }
+
+            let _ = writeln!(lcov, "BRF:{}", branches.len() * 2);
+            let _ = writeln!(lcov, "BRH:{branches_hit}");
+
+            for (line, hits) in &lines {
+                let _ = writeln!(lcov, "DA:{line},{hits}");
+            }
+
+            let _ = writeln!(lcov, "LF:{}", lines.len());
+            let _ = writeln!(
+                lcov,
+                "LH:{}",
+                lines.values().filter(|hits| **hits > 0).count()
+            );
+            let _ = writeln!(lcov, "end_of_record");
+        }
+
+        lcov
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// An environment that the VM talks to: debug output, pins and entropy.
+/// Time is given to the VM with [`crate::VM::advance_clock`].
+pub trait Host {
+    /// Called by `dbg`.
+    fn debug_number(&mut self, value: f32);
+
+    /// Called by `dbgs`.
+    fn debug_string(&mut self, text: &str);
+
+    fn read_pin(&self, id: usize) -> f32;
+
+    fn write_pin(&mut self, id: usize, value: f32);
+
+    /// A seed for the VM's random number generator.
+    fn random_seed(&mut self) -> u64;
+}
+
+/// A host that ignores all output and reads zeros from every pin.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoopHost;
+
+impl Host for NoopHost {
+    fn debug_number(&mut self, _value: f32) {}
+
+    fn debug_string(&mut self, _text: &str) {}
+
+    fn read_pin(&self, _id: usize) -> f32 {
+        0.0
+    }
+
+    fn write_pin(&mut self, _id: usize, _value: f32) {}
+
+    fn random_seed(&mut self) -> u64 {
+        0
+    }
+}
+
+/// A host that prints debug output to stdout.
+#[derive(Debug, Clone)]
+pub struct StdoutHost {
+    pins: BTreeMap<usize, f32>,
+}
+
+impl Default for StdoutHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StdoutHost {
+    pub fn new() -> Self {
+        Self {
+            pins: BTreeMap::new(),
+        }
+    }
+}
+
+impl Host for StdoutHost {
+    fn debug_number(&mut self, value: f32) {
+        println!("{value}")
+    }
+
+    fn debug_string(&mut self, text: &str) {
+        println!("{text}")
+    }
+
+    fn read_pin(&self, id: usize) -> f32 {
+        self.pins.get(&id).copied().unwrap_or_default()
+    }
+
+    fn write_pin(&mut self, id: usize, value: f32) {
+        self.pins.insert(id, value);
+    }
+
+    fn random_seed(&mut self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugOutput {
+    Number { value: f32 },
+    String { value: String },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct CaptureState {
+    output: Vec<DebugOutput>,
+    pins: BTreeMap<usize, f32>,
+    seed: u64,
+}
+
+/// A host that records everything the VM does, useful in tests.
+///
+/// Clones share the same buffer, so one copy can be given to the VM and
+/// another one kept for inspection.
+#[derive(Debug, Clone, Default)]
+pub struct CaptureHost {
+    state: Rc<RefCell<CaptureState>>,
+}
+
+impl CaptureHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn output(&self) -> Vec<DebugOutput> {
+        self.state.borrow().output.clone()
+    }
+
+    pub fn clear_output(&self) {
+        self.state.borrow_mut().output.clear()
+    }
+
+    pub fn pin(&self, id: usize) -> f32 {
+        self.read_pin(id)
+    }
+
+    pub fn set_pin(&self, id: usize, value: f32) {
+        self.state.borrow_mut().pins.insert(id, value);
+    }
+
+    pub fn set_seed(&self, seed: u64) {
+        self.state.borrow_mut().seed = seed;
+    }
+}
+
+impl Host for CaptureHost {
+    fn debug_number(&mut self, value: f32) {
+        self.state
+            .borrow_mut()
+            .output
+            .push(DebugOutput::Number { value })
+    }
+
+    fn debug_string(&mut self, text: &str) {
+        self.state.borrow_mut().output.push(DebugOutput::String {
+            value: text.to_string(),
+        })
+    }
+
+    fn read_pin(&self, id: usize) -> f32 {
+        self.state
+            .borrow()
+            .pins
+            .get(&id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn write_pin(&mut self, id: usize, value: f32) {
+        self.set_pin(id, value)
+    }
+
+    fn random_seed(&mut self) -> u64 {
+        self.state.borrow().seed
+    }
+}
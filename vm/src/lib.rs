@@ -1,11 +1,25 @@
+mod coroutine;
+mod coverage;
+mod host;
+mod profiler;
 mod ram;
+mod rng;
 mod runtime_error;
 mod runtime_error_kind;
+mod sleep;
+mod test_runner;
 mod vm;
 mod vm_status;
 
+pub use coroutine::Coroutine;
+pub use coverage::{BranchCoverage, Coverage};
+pub use host::{CaptureHost, DebugOutput, Host, NoopHost, StdoutHost};
+pub use profiler::{FunctionProfile, InstructionProfile, LabelProfile, ProfileReport, Profiler};
 pub use ram::Ram;
+pub use rng::Rng;
 pub use runtime_error::RuntimeError;
 pub use runtime_error_kind::RuntimeErrorKind;
+pub use sleep::Sleep;
+pub use test_runner::{TestCase, TestOutcome, TestResult, TestRunner};
 pub use vm::VM;
 pub use vm_status::VMStatus;
@@ -0,0 +1,148 @@
+use nano_risc_arch::{Assembly, Location};
+use std::collections::BTreeMap;
+
+/// Counts how often each instruction runs and how much work every called
+/// function does.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profiler {
+    hits: BTreeMap<usize, u64>,
+    calls: BTreeMap<usize, CallStats>,
+    call_stack: Vec<(usize, u64)>,
+    total: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct CallStats {
+    calls: u64,
+    inclusive: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstructionProfile {
+    pub address: usize,
+    pub hits: u64,
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelProfile {
+    /// `None` for instructions before the first label.
+    pub name: Option<String>,
+    pub address: usize,
+    pub hits: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionProfile {
+    pub name: Option<String>,
+    pub address: usize,
+    pub calls: u64,
+    /// Instructions executed inside the function, including its callees.
+    pub inclusive: u64,
+}
+
+/// Profiling results, every list is sorted from the hottest entry.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProfileReport {
+    pub total: u64,
+    pub instructions: Vec<InstructionProfile>,
+    pub labels: Vec<LabelProfile>,
+    pub functions: Vec<FunctionProfile>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default()
+    }
+
+    /// Total number of executed instructions.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn hits(&self, address: usize) -> u64 {
+        self.hits.get(&address).copied().unwrap_or_default()
+    }
+
+    pub(crate) fn record(&mut self, address: usize) {
+        *self.hits.entry(address).or_default() += 1;
+        self.total += 1;
+    }
+
+    pub(crate) fn enter(&mut self, address: usize) {
+        self.calls.entry(address).or_default().calls += 1;
+        self.call_stack.push((address, self.total));
+    }
+
+    pub(crate) fn leave(&mut self) {
+        let Some((address, entered_at)) = self.call_stack.pop() else {
+            return;
+        };
+
+        self.calls.entry(address).or_default().inclusive += self.total - entered_at;
+    }
+
+    pub fn report(&self, assembly: &Assembly) -> ProfileReport {
+        let debug_info = assembly.debug_info.as_ref();
+        let mut instructions: Vec<InstructionProfile> = self
+            .hits
+            .iter()
+            .map(|(address, hits)| InstructionProfile {
+                address: *address,
+                hits: *hits,
+                location: debug_info.and_then(|info| info.source_loc.get(address).copied()),
+            })
+            .collect();
+        let mut labels = BTreeMap::<usize, LabelProfile>::new();
+
+        for (address, hits) in &self.hits {
+            let label = debug_info.and_then(|info| info.label_for(*address));
+            let start = label.map(|(_, start)| start).unwrap_or_default();
+
+            labels
+                .entry(start)
+                .or_insert_with(|| LabelProfile {
+                    name: label.map(|(name, _)| name.to_string()),
+                    address: start,
+                    hits: 0,
+                })
+                .hits += hits;
+        }
+
+        let mut labels: Vec<LabelProfile> = labels.into_values().collect();
+        let mut functions: Vec<FunctionProfile> = self
+            .calls
+            .iter()
+            .map(|(address, stats)| FunctionProfile {
+                name: debug_info.and_then(|info| {
+                    info.labels
+                        .iter()
+                        .find(|(_, label)| *label == address)
+                        .map(|(name, _)| name.clone())
+                }),
+                address: *address,
+                calls: stats.calls,
+                inclusive: stats.inclusive,
+            })
+            .collect();
+
+        instructions.sort_by(|a, b| b.hits.cmp(&a.hits).then(a.address.cmp(&b.address)));
+        labels.sort_by(|a, b| b.hits.cmp(&a.hits).then(a.address.cmp(&b.address)));
+        functions.sort_by(|a, b| {
+            b.inclusive
+                .cmp(&a.inclusive)
+                .then(a.address.cmp(&b.address))
+        });
+
+        ProfileReport {
+            total: self.total,
+            instructions,
+            labels,
+            functions,
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+/// A SplitMix64 pseudo random number generator.
+///
+/// The whole state is two integers, so it can be saved and restored to
+/// replay a program deterministically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rng {
+    seed: u64,
+    state: u64,
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// Restarts the sequence from the seed.
+    pub fn reset(&mut self) {
+        self.state = self.seed;
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        z ^ (z >> 31)
+    }
+
+    /// Returns a float in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns a float in `[min, max)`, or `min` when the range is empty.
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        let t = self.next_f32();
+
+        if min.partial_cmp(&max) != Some(Ordering::Less) {
+            return min;
+        }
+
+        // Interpolating this way doesn't overflow for ranges wider than
+        // `f32::MAX`, rounding can still reach `max` so it's clamped.
+        (min * (1.0 - t) + max * t).clamp(min, max.next_down())
+    }
+
+    /// Returns an integer in `[min, max]`.
+    pub fn range_i32(&mut self, min: i32, max: i32) -> i32 {
+        let (min, max) = (min.min(max), min.max(max));
+        let span = (max as i64 - min as i64 + 1) as u64;
+
+        (min as i64 + (self.next_u64() % span) as i64) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rng;
+
+    #[test]
+    fn deterministic() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+
+        for _ in 0..16 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+
+        a.reset();
+        b = Rng::new(42);
+
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut rng = Rng::new(7);
+
+        for _ in 0..1000 {
+            let value = rng.range_f32(-2.0, 3.0);
+            assert!((-2.0..3.0).contains(&value));
+
+            let value = rng.range_i32(5, -5);
+            assert!((-5..=5).contains(&value));
+        }
+    }
+
+    #[test]
+    fn float_range_extremes() {
+        let mut rng = Rng::new(7);
+
+        for _ in 0..1000 {
+            let value = rng.range_f32(f32::MIN, f32::MAX);
+            assert!(value.is_finite() && value < f32::MAX);
+
+            let value = rng.range_f32(1.0, 1.0 + f32::EPSILON);
+            assert_eq!(value, 1.0);
+
+            let value = rng.range_f32(1e30, 1e30 + 1e24);
+            assert!((1e30..1e30 + 1e24).contains(&value));
+        }
+
+        assert_eq!(rng.range_f32(3.0, 3.0), 3.0);
+    }
+}
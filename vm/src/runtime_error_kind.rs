@@ -10,4 +10,9 @@ pub enum RuntimeErrorKind {
     InvalidAddress { address: usize },
     StackOverflow,
     OutOfMemory,
+    InvalidFormat,
+    InvalidCoroutine { id: usize },
+    TooManyCoroutines,
+    Deadlock,
+    AssertionFailed,
 }
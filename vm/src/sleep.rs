@@ -0,0 +1,12 @@
+use nano_risc_arch::Argument;
+
+/// Why a sleeping VM is suspended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sleep {
+    /// Until the clock reaches the given tick.
+    Ticks { until: u64 },
+    /// Until the clock reaches the given time in milliseconds.
+    Millis { until: u64 },
+    /// Until the argument becomes non-zero.
+    Condition { argument: Argument },
+}
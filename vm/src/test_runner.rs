@@ -0,0 +1,158 @@
+use crate::{CaptureHost, DebugOutput, RuntimeError, VMStatus, VM};
+use nano_risc_arch::{Assembly, AssemblyError, Limits, Location, RegisterKind};
+
+/// Runs the tests of an assembly.
+///
+/// Every label starting with [`TestRunner::PREFIX`] is a test. Tests are
+/// called like functions in a fresh [`VM`], so a test passes once it
+/// returns with `ret` or stops with `halt` without raising an error.
+#[derive(Debug, Clone)]
+pub struct TestRunner {
+    assembly: Assembly,
+    limits: Limits,
+    max_ticks: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCase {
+    pub name: String,
+    pub address: usize,
+    pub location: Option<Location>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TestOutcome {
+    Passed,
+    Failed {
+        error: RuntimeError,
+        /// Location of the instruction that raised the error.
+        location: Option<Location>,
+    },
+    /// The test didn't finish in [`TestRunner::max_ticks`] ticks.
+    TimedOut,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    pub case: TestCase,
+    pub outcome: TestOutcome,
+    /// Everything the test printed with `dbg`, `dbgs` and `dbgf`.
+    pub output: Vec<DebugOutput>,
+    pub ticks: u64,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.outcome == TestOutcome::Passed
+    }
+}
+
+impl TestRunner {
+    pub const PREFIX: &'static str = "test_";
+
+    pub fn new(assembly: Assembly, limits: Limits) -> Result<Self, AssemblyError> {
+        VM::new(limits.clone()).load_assembly(assembly.clone())?;
+
+        Ok(Self {
+            assembly,
+            limits,
+            max_ticks: 1_000_000,
+        })
+    }
+
+    pub fn assembly(&self) -> &Assembly {
+        &self.assembly
+    }
+
+    pub fn max_ticks(&self) -> u64 {
+        self.max_ticks
+    }
+
+    pub fn set_max_ticks(&mut self, max_ticks: u64) {
+        self.max_ticks = max_ticks
+    }
+
+    /// Finds the tests ordered by their address. An assembly without debug
+    /// info has no label names, so it has no tests. Local labels inside a
+    /// test, e.g. `test_count@loop`, aren't tests of their own.
+    pub fn tests(&self) -> Vec<TestCase> {
+        let Some(debug_info) = self.assembly.debug_info.as_ref() else {
+            return Vec::new();
+        };
+        let mut tests: Vec<TestCase> = debug_info
+            .labels
+            .iter()
+            .filter(|(name, _)| name.starts_with(Self::PREFIX) && !name.contains('@'))
+            .map(|(name, address)| TestCase {
+                name: name.clone(),
+                address: *address,
+                location: debug_info.source_loc.get(address).copied(),
+            })
+            .collect();
+
+        tests.sort_by_key(|test| test.address);
+
+        tests
+    }
+
+    pub fn run_all(&self) -> Vec<TestResult> {
+        self.tests()
+            .into_iter()
+            .map(|test| self.run(test))
+            .collect()
+    }
+
+    pub fn run(&self, case: TestCase) -> TestResult {
+        let host = CaptureHost::new();
+        let mut vm = VM::new(self.limits.clone());
+        let mut ticks = 0;
+
+        vm.set_host(Box::new(host.clone()));
+
+        let outcome = match Self::start(&mut vm, &self.assembly, case.address) {
+            Ok(()) => loop {
+                if ticks >= self.max_ticks {
+                    break TestOutcome::TimedOut;
+                }
+
+                ticks += 1;
+
+                match vm.tick() {
+                    Ok(VMStatus::Finished | VMStatus::Idle) => break TestOutcome::Passed,
+                    Ok(VMStatus::Yield | VMStatus::Sleeping) => vm.advance_clock(1),
+                    Ok(_) => {}
+                    Err(error) => {
+                        break TestOutcome::Failed {
+                            error,
+                            location: self
+                                .assembly
+                                .debug_info
+                                .as_ref()
+                                .and_then(|info| info.source_loc.get(&vm.pc()).copied()),
+                        }
+                    }
+                }
+            },
+            Err(error) => TestOutcome::Failed {
+                error,
+                location: case.location,
+            },
+        };
+
+        TestResult {
+            case,
+            outcome,
+            output: host.output(),
+            ticks,
+        }
+    }
+
+    /// Loads the assembly and calls the test, its `ret` jumps right past
+    /// the last instruction, which finishes the program.
+    fn start(vm: &mut VM, assembly: &Assembly, address: usize) -> Result<(), RuntimeError> {
+        vm.load_assembly(assembly.clone())
+            .expect("The assembly was loaded by `TestRunner::new`");
+        vm.push_stack(assembly.instructions.len() as f32)?;
+        vm.write_register(RegisterKind::ProgramCounter, address as f32)
+    }
+}
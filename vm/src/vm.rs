@@ -1,10 +1,12 @@
-use crate::{Ram, RuntimeError, RuntimeErrorKind, VMStatus};
+use crate::{
+    Coroutine, Coverage, Host, NoopHost, Profiler, Ram, Rng, RuntimeError, RuntimeErrorKind, Sleep,
+    VMStatus,
+};
 use nano_risc_arch::{
+    format::{self, FormatSegment},
     Argument, Assembly, AssemblyError, Instruction, Limits, Operation, RegisterKind, RegisterMode,
 };
-use std::{cmp::Ordering, fmt::Debug};
-
-pub type DbgCallback = Box<dyn Fn(String)>;
+use std::{cmp::Ordering, collections::BTreeMap, fmt::Debug, mem};
 
 pub struct VM {
     limits: Limits,
@@ -13,9 +15,20 @@ pub struct VM {
     assembly: Option<Assembly>,
     pc: usize,
     sp: usize,
-    dbg_callback: Option<DbgCallback>,
+    host: Box<dyn Host>,
     status: VMStatus,
     ram: Ram,
+    rng: Rng,
+    coroutine: usize,
+    coroutines: BTreeMap<usize, Coroutine>,
+    next_coroutine: usize,
+    context_switched: bool,
+    sleep: Option<Sleep>,
+    clock_ticks: u64,
+    clock_millis: u64,
+    profiler: Option<Profiler>,
+    coverage: Option<Coverage>,
+    branch_taken: Option<bool>,
 }
 
 impl Default for VM {
@@ -34,6 +47,14 @@ impl Debug for VM {
             .field("pc", &self.pc)
             .field("sp", &self.sp)
             .field("status", &self.status)
+            .field("rng", &self.rng)
+            .field("coroutine", &self.coroutine)
+            .field("coroutines", &self.coroutines)
+            .field("sleep", &self.sleep)
+            .field("clock_ticks", &self.clock_ticks)
+            .field("clock_millis", &self.clock_millis)
+            .field("profiler", &self.profiler)
+            .field("coverage", &self.coverage)
             .finish()
     }
 }
@@ -73,9 +94,20 @@ impl VM {
             assembly: None,
             pc: 0,
             sp: 0,
-            dbg_callback: None,
+            host: Box::new(NoopHost),
             status: VMStatus::Idle,
             ram,
+            rng: Rng::default(),
+            coroutine: 0,
+            coroutines: BTreeMap::new(),
+            next_coroutine: 1,
+            context_switched: false,
+            sleep: None,
+            clock_ticks: 0,
+            clock_millis: 0,
+            profiler: None,
+            coverage: None,
+            branch_taken: None,
         }
     }
 
@@ -124,6 +156,14 @@ impl VM {
         for stack in &mut self.stack {
             *stack = 0.0;
         }
+
+        self.rng.reset();
+        self.coroutine = 0;
+        self.coroutines.clear();
+        self.next_coroutine = 1;
+        self.sleep = None;
+        self.clock_ticks = 0;
+        self.clock_millis = 0;
     }
 
     pub fn registers(&self) -> &[f32] {
@@ -142,12 +182,97 @@ impl VM {
         &self.stack
     }
 
+    /// Id of the running coroutine, the main one is `0`.
+    pub fn coroutine(&self) -> usize {
+        self.coroutine
+    }
+
+    /// Suspended coroutines.
+    pub fn coroutines(&self) -> &BTreeMap<usize, Coroutine> {
+        &self.coroutines
+    }
+
+    /// Why the running coroutine is sleeping.
+    pub fn sleep(&self) -> Option<Sleep> {
+        self.sleep
+    }
+
+    /// Host ticks passed since the VM was reset.
+    pub fn clock_ticks(&self) -> u64 {
+        self.clock_ticks
+    }
+
+    /// Milliseconds passed since the VM was reset.
+    pub fn clock_millis(&self) -> u64 {
+        self.clock_millis
+    }
+
+    /// Advances the clock by one host tick that took `elapsed_millis`.
+    pub fn advance_clock(&mut self, elapsed_millis: u64) {
+        self.clock_ticks += 1;
+        self.clock_millis += elapsed_millis;
+    }
+
+    /// Starts collecting execution statistics, see [`Profiler`].
+    pub fn enable_profiler(&mut self) {
+        self.profiler = Some(Profiler::new())
+    }
+
+    pub fn disable_profiler(&mut self) {
+        self.profiler = None
+    }
+
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    /// Starts recording executed instructions and branches, see [`Coverage`].
+    pub fn enable_coverage(&mut self) {
+        self.coverage = Some(Coverage::new())
+    }
+
+    pub fn disable_coverage(&mut self) {
+        self.coverage = None
+    }
+
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
     pub fn status(&self) -> VMStatus {
         self.status
     }
 
-    pub fn set_dbg_callback(&mut self, callback: DbgCallback) {
-        self.dbg_callback = Some(callback)
+    pub fn set_host(&mut self, host: Box<dyn Host>) {
+        self.host = host
+    }
+
+    pub fn host(&self) -> &dyn Host {
+        self.host.as_ref()
+    }
+
+    pub fn host_mut(&mut self) -> &mut dyn Host {
+        self.host.as_mut()
+    }
+
+    pub fn rng(&self) -> &Rng {
+        &self.rng
+    }
+
+    /// Replaces the random number generator, e.g. to restore a saved state.
+    pub fn set_rng(&mut self, rng: Rng) {
+        self.rng = rng
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed)
+    }
+
+    /// Seeds the random number generator from the host.
+    pub fn reseed(&mut self) {
+        let seed = self.host.random_seed();
+
+        self.set_seed(seed)
     }
 
     /// Executes 1 instruction.
@@ -157,6 +282,22 @@ impl VM {
             _ => {}
         }
 
+        if let Some(sleep) = self.sleep {
+            if !self.is_awake(&sleep)? {
+                // Another coroutine runs while this one sleeps.
+                let Some(id) = self.awake_coroutine()? else {
+                    self.status = VMStatus::Sleeping;
+
+                    return Ok(self.status);
+                };
+
+                self.suspend(self.pc, None, Some(sleep));
+                self.resume(id);
+            }
+
+            self.sleep = None;
+        }
+
         let Some(program) = self.assembly.as_ref() else {
             self.status = VMStatus::Idle;
 
@@ -164,6 +305,12 @@ impl VM {
         };
 
         match self.pc.cmp(&program.instructions.len()) {
+            Ordering::Equal if self.coroutine != 0 => {
+                self.finish_coroutine()?;
+                self.status = VMStatus::Running;
+
+                return Ok(self.status);
+            }
             Ordering::Equal => {
                 self.status = VMStatus::Finished;
 
@@ -183,6 +330,8 @@ impl VM {
         };
 
         let old_pc = self.pc;
+        self.context_switched = false;
+        self.branch_taken = None;
         let Instruction {
             operation: op,
             arguments: args,
@@ -192,13 +341,32 @@ impl VM {
         let status =
             Self::execute_instruction(unsafe { &mut *(self as *const VM as *mut VM) }, *op, args)?;
 
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.record(old_pc);
+
+            match op {
+                Operation::Call => profiler.enter(self.pc),
+                Operation::Ret => profiler.leave(),
+                _ => {}
+            }
+        }
+
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.record(old_pc, self.branch_taken);
+        }
+
         if let Some(status) = status {
             self.status = status;
         } else {
             self.status = VMStatus::Running;
         }
 
-        if matches!(self.status, VMStatus::Running | VMStatus::Yield) && old_pc == self.pc() {
+        if matches!(
+            self.status,
+            VMStatus::Running | VMStatus::Yield | VMStatus::Sleeping
+        ) && !self.context_switched
+            && old_pc == self.pc()
+        {
             self.write_register(RegisterKind::ProgramCounter, (old_pc + 1) as f32)?;
         }
 
@@ -233,9 +401,9 @@ impl VM {
                 }
             }
             RegisterKind::ProgramCounter => self.pc = value as usize,
-            RegisterKind::StackPointer => {
+            RegisterKind::StackPointer | RegisterKind::Clock => {
                 return Err(RuntimeError::new(
-                    String::from("sp is read-only"),
+                    format!("{register} is read-only"),
                     RuntimeErrorKind::RegisterIsReadOnly { register },
                 ))
             }
@@ -330,16 +498,18 @@ impl VM {
                 self.write_register(register, result)?;
             }
             Operation::Mov => {
-                let Argument::Register { register } = args[0] else {
-                    return Err(RuntimeError::new(
-                        String::from("Expected register"),
-                        RuntimeErrorKind::InvalidType,
-                    ));
-                };
-
                 let a = self.argument_to_float(&args[1])?;
 
-                self.write_register(register, a)?
+                match args[0] {
+                    Argument::Register { register } => self.write_register(register, a)?,
+                    Argument::Pin { id } => self.host.write_pin(id, a),
+                    _ => {
+                        return Err(RuntimeError::new(
+                            String::from("Expected register or pin"),
+                            RuntimeErrorKind::InvalidType,
+                        ))
+                    }
+                }
             }
             Operation::Jmp => {
                 let value = self.argument_to_float(&args[0])?;
@@ -347,38 +517,88 @@ impl VM {
                 self.write_register(RegisterKind::ProgramCounter, value)?
             }
             Operation::Dbg => {
-                let Some(callback) = &self.dbg_callback else {
-                    return Ok(None);
-                };
-
                 let value = self.argument_to_float(&args[0])?;
 
-                callback(value.to_string())
+                self.host.debug_number(value)
             }
             Operation::Dbgs => {
-                let Some(callback) = &self.dbg_callback else {
-                    return Ok(None);
-                };
-                let Some(assembly) = self.assembly.as_ref() else {
-                    unreachable!()
-                };
-                let address = (self.argument_to_float(&args[0])? as usize)
-                    .saturating_sub(assembly.code_section_size);
-                let mut text_bytes = Vec::new();
-                let mut idx = address;
+                let text = self.read_string(&args[0])?;
 
-                while let Ok(b) = self.ram().read(idx) {
-                    if b == 0 {
-                        break;
+                self.host.debug_string(&text)
+            }
+            Operation::Dbgf => {
+                let format = self.read_string(&args[0])?;
+                let segments = format::parse(&format).map_err(|err| {
+                    RuntimeError::new(
+                        format!("Invalid format string: {}", err.message),
+                        RuntimeErrorKind::InvalidFormat,
+                    )
+                })?;
+                let mut values = args[1..].iter();
+                let mut text = String::new();
+
+                for segment in segments {
+                    match segment {
+                        FormatSegment::Text { text: part } => text.push_str(&part),
+                        FormatSegment::Placeholder { spec } => {
+                            let Some(value) = values.next() else {
+                                return Err(RuntimeError::new(
+                                    String::from("Not enough arguments for the format string"),
+                                    RuntimeErrorKind::InvalidFormat,
+                                ));
+                            };
+
+                            text.push_str(&format::format_value(
+                                spec,
+                                self.argument_to_float(value)?,
+                            ));
+                        }
                     }
-
-                    text_bytes.push(b);
-                    idx += 1;
                 }
 
-                callback(String::from_utf8_lossy(&text_bytes).to_string())
+                self.host.debug_string(&text)
             }
             Operation::Yield => return Ok(Some(VMStatus::Yield)),
+            Operation::Sleep | Operation::Sleepms => {
+                let duration = self.argument_to_float(&args[0])?.max(0.0) as u64;
+
+                return self.fall_asleep(match operation {
+                    Operation::Sleep => Sleep::Ticks {
+                        until: self.clock_ticks + duration,
+                    },
+                    Operation::Sleepms => Sleep::Millis {
+                        until: self.clock_millis + duration,
+                    },
+                    _ => unreachable!(),
+                });
+            }
+            Operation::Wait => {
+                if self.argument_to_float(&args[0])? == 0.0 {
+                    return self.fall_asleep(Sleep::Condition { argument: args[0] });
+                }
+            }
+            Operation::Assert => {
+                if self.argument_to_float(&args[0])? == 0.0 {
+                    return Err(self.assertion_failed(args.get(1), None)?);
+                }
+            }
+            Operation::Asserteq | Operation::Assertne => {
+                let found = self.argument_to_float(&args[0])?;
+                let expected = self.argument_to_float(&args[1])?;
+                let failed = match operation {
+                    Operation::Asserteq => found != expected,
+                    _ => found == expected,
+                };
+
+                if failed {
+                    let details = match operation {
+                        Operation::Asserteq => format!("expected {expected}, found {found}"),
+                        _ => format!("both are {found}"),
+                    };
+
+                    return Err(self.assertion_failed(args.get(2), Some(details))?);
+                }
+            }
             Operation::Beq
             | Operation::Bge
             | Operation::Bgt
@@ -398,6 +618,8 @@ impl VM {
                     _ => unreachable!(),
                 };
 
+                self.branch_taken = Some(result);
+
                 if result {
                     self.write_register(
                         RegisterKind::ProgramCounter,
@@ -423,6 +645,8 @@ impl VM {
                     _ => unreachable!(),
                 };
 
+                self.branch_taken = Some(result);
+
                 if result {
                     self.write_register(
                         RegisterKind::ProgramCounter,
@@ -509,11 +733,86 @@ impl VM {
                 let ret = self.peek_stack()?;
                 self.write_register(*register, ret)?;
             }
+            Operation::Ret if self.sp == 0 && self.coroutine != 0 => self.finish_coroutine()?,
             Operation::Ret => {
                 let ret = self.pop_stack()?;
 
                 self.write_register(RegisterKind::ProgramCounter, ret)?;
             }
+            Operation::Spawn => {
+                let Argument::Register { register } = &args[0] else {
+                    return Err(RuntimeError::new(
+                        String::from("Expected register"),
+                        RuntimeErrorKind::InvalidType,
+                    ));
+                };
+                let pc = self.argument_to_float(&args[1])? as usize;
+
+                if self.coroutines.len() + 1 >= self.limits.coroutines {
+                    return Err(RuntimeError::new(
+                        format!(
+                            "Can't spawn more than {} coroutines",
+                            self.limits.coroutines
+                        ),
+                        RuntimeErrorKind::TooManyCoroutines,
+                    ));
+                }
+
+                let id = self.next_coroutine;
+
+                self.next_coroutine += 1;
+                self.coroutines
+                    .insert(id, Coroutine::new(pc, self.limits.stack_size));
+                self.write_register(*register, id as f32)?;
+            }
+            Operation::Switch => {
+                let id = self.argument_to_float(&args[0])? as usize;
+
+                if id != self.coroutine {
+                    self.switch_coroutine(id)?;
+                }
+            }
+            Operation::Join => {
+                let id = self.argument_to_float(&args[0])? as usize;
+
+                if id == self.coroutine {
+                    return Err(RuntimeError::new(
+                        String::from("A coroutine can't join itself"),
+                        RuntimeErrorKind::InvalidCoroutine { id },
+                    ));
+                }
+
+                if self.coroutines.contains_key(&id) {
+                    self.join_coroutine(id)?;
+                }
+            }
+            Operation::Cancel => {
+                let id = self.argument_to_float(&args[0])? as usize;
+
+                if id == self.coroutine || id == 0 {
+                    return Err(RuntimeError::new(
+                        format!("Coroutine {id} can't be cancelled"),
+                        RuntimeErrorKind::InvalidCoroutine { id },
+                    ));
+                }
+
+                // Cancelling a finished coroutine does nothing, but one that
+                // was never spawned is a mistake.
+                if id >= self.next_coroutine {
+                    return Err(RuntimeError::new(
+                        format!("Coroutine {id} does not exist"),
+                        RuntimeErrorKind::InvalidCoroutine { id },
+                    ));
+                }
+
+                self.coroutines.remove(&id);
+
+                for coroutine in self.coroutines.values_mut() {
+                    if coroutine.waiting_for == Some(id) {
+                        coroutine.waiting_for = None;
+                    }
+                }
+            }
             Operation::Call => {
                 let a = self.argument_to_float(&args[0])?;
 
@@ -588,7 +887,13 @@ impl VM {
             | Operation::Abs
             | Operation::Exp
             | Operation::Inf
-            | Operation::Nan => {
+            | Operation::Nan
+            | Operation::Sin
+            | Operation::Cos
+            | Operation::Tan
+            | Operation::Asin
+            | Operation::Acos
+            | Operation::Sign => {
                 let Argument::Register { register } = &args[0] else {
                     return Err(RuntimeError::new(
                         String::from("Expected register"),
@@ -618,12 +923,30 @@ impl VM {
                             0.0
                         }
                     }
+                    Operation::Sin => a.sin(),
+                    Operation::Cos => a.cos(),
+                    Operation::Tan => a.tan(),
+                    Operation::Asin => a.asin(),
+                    Operation::Acos => a.acos(),
+                    Operation::Sign => {
+                        if a > 0.0 {
+                            1.0
+                        } else if a < 0.0 {
+                            -1.0
+                        } else {
+                            a
+                        }
+                    }
                     _ => unreachable!(),
                 };
 
                 self.write_register(*register, result)?;
             }
-            Operation::Max | Operation::Min | Operation::Log => {
+            Operation::Max
+            | Operation::Min
+            | Operation::Log
+            | Operation::Atan2
+            | Operation::Pow => {
                 let Argument::Register { register } = &args[0] else {
                     return Err(RuntimeError::new(
                         String::from("Expected register"),
@@ -637,6 +960,123 @@ impl VM {
                     Operation::Max => f32::max(a, b),
                     Operation::Min => f32::min(a, b),
                     Operation::Log => f32::log(b, a),
+                    Operation::Atan2 => f32::atan2(a, b),
+                    Operation::Pow => f32::powf(a, b),
+                    _ => unreachable!(),
+                };
+
+                self.write_register(*register, result)?;
+            }
+            Operation::Lerp | Operation::Clamp | Operation::Select => {
+                let Argument::Register { register } = &args[0] else {
+                    return Err(RuntimeError::new(
+                        String::from("Expected register"),
+                        RuntimeErrorKind::InvalidType,
+                    ));
+                };
+                let a = self.argument_to_float(&args[1])?;
+                let b = self.argument_to_float(&args[2])?;
+                let c = self.argument_to_float(&args[3])?;
+
+                let result = match operation {
+                    Operation::Lerp => a + (b - a) * c,
+                    Operation::Clamp => f32::max(b, f32::min(a, c)),
+                    Operation::Select => {
+                        if a != 0.0 {
+                            b
+                        } else {
+                            c
+                        }
+                    }
+                    _ => unreachable!(),
+                };
+
+                self.write_register(*register, result)?;
+            }
+            Operation::Vadd { size } | Operation::Vsub { size } | Operation::Vscale { size } => {
+                let a = self.read_vector(&args[1], size)?;
+                let mut result = [0.0; 4];
+
+                match operation {
+                    Operation::Vadd { .. } | Operation::Vsub { .. } => {
+                        let b = self.read_vector(&args[2], size)?;
+
+                        for i in 0..size {
+                            result[i] = if matches!(operation, Operation::Vadd { .. }) {
+                                a[i] + b[i]
+                            } else {
+                                a[i] - b[i]
+                            };
+                        }
+                    }
+                    Operation::Vscale { .. } => {
+                        let b = self.argument_to_float(&args[2])?;
+
+                        for i in 0..size {
+                            result[i] = a[i] * b;
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+
+                self.write_vector(&args[0], &result[..size])?;
+            }
+            Operation::Vcross => {
+                let a = self.read_vector(&args[1], 3)?;
+                let b = self.read_vector(&args[2], 3)?;
+                let result = [
+                    a[1] * b[2] - a[2] * b[1],
+                    a[2] * b[0] - a[0] * b[2],
+                    a[0] * b[1] - a[1] * b[0],
+                ];
+
+                self.write_vector(&args[0], &result)?;
+            }
+            Operation::Vnorm { size } => {
+                let a = self.read_vector(&args[1], size)?;
+                let length = Self::dot(&a, &a, size).sqrt();
+                let mut result = [0.0; 4];
+
+                if length != 0.0 {
+                    for i in 0..size {
+                        result[i] = a[i] / length;
+                    }
+                }
+
+                self.write_vector(&args[0], &result[..size])?;
+            }
+            Operation::Vdot { size } | Operation::Vlen { size } => {
+                let Argument::Register { register } = &args[0] else {
+                    return Err(RuntimeError::new(
+                        String::from("Expected register"),
+                        RuntimeErrorKind::InvalidType,
+                    ));
+                };
+                let a = self.read_vector(&args[1], size)?;
+
+                let result = match operation {
+                    Operation::Vdot { .. } => {
+                        Self::dot(&a, &self.read_vector(&args[2], size)?, size)
+                    }
+                    Operation::Vlen { .. } => Self::dot(&a, &a, size).sqrt(),
+                    _ => unreachable!(),
+                };
+
+                self.write_register(*register, result)?;
+            }
+            Operation::Rand | Operation::Randi => {
+                let Argument::Register { register } = &args[0] else {
+                    return Err(RuntimeError::new(
+                        String::from("Expected register"),
+                        RuntimeErrorKind::InvalidType,
+                    ));
+                };
+                let a = self.argument_to_float(&args[1])?;
+                let b = self.argument_to_float(&args[2])?;
+
+                let result = match operation {
+                    Operation::Rand => self.rng.range_f32(a, b),
+                    Operation::Randi => self.rng.range_i32(a as i32, b as i32) as f32,
                     _ => unreachable!(),
                 };
 
@@ -727,6 +1167,268 @@ impl VM {
         Ok(None)
     }
 
+    /// Suspends the running coroutine after the current instruction and
+    /// resumes coroutine `id`. A coroutine blocked joining another one can't
+    /// be switched to.
+    fn switch_coroutine(&mut self, id: usize) -> Result<(), RuntimeError> {
+        let Some(target) = self.coroutines.get(&id) else {
+            return Err(RuntimeError::new(
+                format!("Coroutine {id} does not exist"),
+                RuntimeErrorKind::InvalidCoroutine { id },
+            ));
+        };
+
+        if let Some(joined) = target.waiting_for {
+            return Err(RuntimeError::new(
+                format!("Coroutine {id} is waiting for coroutine {joined} to finish"),
+                RuntimeErrorKind::InvalidCoroutine { id },
+            ));
+        }
+
+        self.suspend(self.pc + 1, None, None);
+        self.resume(id);
+
+        Ok(())
+    }
+
+    /// Blocks the running coroutine until coroutine `id` finishes, running
+    /// `id` or, when it's blocked too, another coroutine in the meantime.
+    fn join_coroutine(&mut self, id: usize) -> Result<(), RuntimeError> {
+        self.suspend(self.pc + 1, Some(id), None);
+
+        let next = match self.coroutines.get(&id) {
+            Some(target) if target.waiting_for.is_none() => Some(id),
+            _ => self.runnable_coroutine()?,
+        };
+
+        self.resume_or_deadlock(next)
+    }
+
+    /// Drops the running coroutine and resumes the one that joined it, or
+    /// the first one that isn't waiting for anything.
+    fn finish_coroutine(&mut self) -> Result<(), RuntimeError> {
+        let finished = self.coroutine;
+        let mut joined = None;
+
+        for (id, coroutine) in self.coroutines.iter_mut() {
+            if coroutine.waiting_for == Some(finished) {
+                coroutine.waiting_for = None;
+                joined = joined.or(Some(*id));
+            }
+        }
+
+        let next = match joined {
+            Some(id) => Some(id),
+            None => self.runnable_coroutine()?,
+        };
+
+        self.resume_or_deadlock(next)
+    }
+
+    /// Puts the running coroutine to sleep, another one that's awake runs in
+    /// the meantime. The VM sleeps when there is none.
+    fn fall_asleep(&mut self, sleep: Sleep) -> Result<Option<VMStatus>, RuntimeError> {
+        let Some(id) = self.awake_coroutine()? else {
+            self.sleep = Some(sleep);
+
+            return Ok(Some(VMStatus::Sleeping));
+        };
+
+        self.suspend(self.pc + 1, None, Some(sleep));
+        self.resume(id);
+
+        Ok(None)
+    }
+
+    fn is_awake(&self, sleep: &Sleep) -> Result<bool, RuntimeError> {
+        Ok(match sleep {
+            Sleep::Ticks { until } => self.clock_ticks >= *until,
+            Sleep::Millis { until } => self.clock_millis >= *until,
+            Sleep::Condition { argument } => self.argument_to_float(argument)? != 0.0,
+        })
+    }
+
+    /// The first suspended coroutine that can run right away.
+    fn awake_coroutine(&self) -> Result<Option<usize>, RuntimeError> {
+        for (id, coroutine) in &self.coroutines {
+            if coroutine.waiting_for.is_some() {
+                continue;
+            }
+
+            match &coroutine.sleep {
+                Some(sleep) if !self.is_awake(sleep)? => {}
+                _ => return Ok(Some(*id)),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The first suspended coroutine that isn't waiting for another one,
+    /// preferring the ones that are awake.
+    fn runnable_coroutine(&self) -> Result<Option<usize>, RuntimeError> {
+        Ok(self.awake_coroutine()?.or_else(|| {
+            self.coroutines
+                .iter()
+                .find(|(_, coroutine)| coroutine.waiting_for.is_none())
+                .map(|(id, _)| *id)
+        }))
+    }
+
+    fn resume_or_deadlock(&mut self, next: Option<usize>) -> Result<(), RuntimeError> {
+        let Some(id) = next else {
+            let waiting: Vec<String> = self
+                .coroutines
+                .iter()
+                .filter_map(|(id, coroutine)| {
+                    coroutine
+                        .waiting_for
+                        .map(|joined| format!("{id} joins {joined}"))
+                })
+                .collect();
+
+            return Err(RuntimeError::new(
+                format!(
+                    "Deadlock, every coroutine is waiting for another one: {}",
+                    waiting.join(", ")
+                ),
+                RuntimeErrorKind::Deadlock,
+            ));
+        };
+
+        self.resume(id);
+
+        Ok(())
+    }
+
+    /// Saves the running coroutine, it continues from `pc` once resumed.
+    fn suspend(&mut self, pc: usize, waiting_for: Option<usize>, sleep: Option<Sleep>) {
+        let current = Coroutine {
+            pc,
+            sp: self.sp,
+            stack: mem::take(&mut self.stack),
+            waiting_for,
+            sleep,
+        };
+
+        self.coroutines.insert(self.coroutine, current);
+    }
+
+    fn resume(&mut self, id: usize) {
+        let coroutine = self
+            .coroutines
+            .remove(&id)
+            .expect("Resumed coroutines are suspended");
+
+        self.coroutine = id;
+        self.pc = coroutine.pc;
+        self.sp = coroutine.sp;
+        self.stack = coroutine.stack;
+        self.sleep = coroutine.sleep;
+        self.context_switched = true;
+    }
+
+    /// Reads `size` consecutive registers starting from `argument`.
+    fn read_vector(&self, argument: &Argument, size: usize) -> Result<[f32; 4], RuntimeError> {
+        let Argument::Register {
+            register: RegisterKind::Regular { id, .. },
+        } = argument
+        else {
+            return Err(RuntimeError::new(
+                String::from("Expected register"),
+                RuntimeErrorKind::InvalidType,
+            ));
+        };
+        let Some(registers) = self.registers.get(*id..*id + size) else {
+            return Err(RuntimeError::new(
+                format!("Registers R{id}..R{} are out of bounds", id + size - 1),
+                RuntimeErrorKind::InvalidRegister {
+                    register: RegisterKind::Regular {
+                        id: id + size - 1,
+                        mode: RegisterMode::Direct,
+                    },
+                },
+            ));
+        };
+        let mut vector = [0.0; 4];
+
+        vector[..size].copy_from_slice(registers);
+
+        Ok(vector)
+    }
+
+    /// Writes `vector` into consecutive registers starting from `argument`.
+    fn write_vector(&mut self, argument: &Argument, vector: &[f32]) -> Result<(), RuntimeError> {
+        let Argument::Register {
+            register: RegisterKind::Regular { id, .. },
+        } = argument
+        else {
+            return Err(RuntimeError::new(
+                String::from("Expected register"),
+                RuntimeErrorKind::InvalidType,
+            ));
+        };
+
+        for (offset, value) in vector.iter().enumerate() {
+            self.write_register(
+                RegisterKind::Regular {
+                    id: id + offset,
+                    mode: RegisterMode::Direct,
+                },
+                *value,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn dot(a: &[f32; 4], b: &[f32; 4], size: usize) -> f32 {
+        a.iter().zip(b).take(size).map(|(a, b)| a * b).sum()
+    }
+
+    /// Reads a null-terminated string from the text section.
+    fn read_string(&self, argument: &Argument) -> Result<String, RuntimeError> {
+        let Some(assembly) = self.assembly.as_ref() else {
+            unreachable!()
+        };
+        let address =
+            (self.argument_to_float(argument)? as usize).saturating_sub(assembly.code_section_size);
+        let mut text_bytes = Vec::new();
+        let mut idx = address;
+
+        while let Ok(b) = self.ram().read(idx) {
+            if b == 0 {
+                break;
+            }
+
+            text_bytes.push(b);
+            idx += 1;
+        }
+
+        Ok(String::from_utf8_lossy(&text_bytes).to_string())
+    }
+
+    /// Builds the error raised by a failed assertion, `message` is the
+    /// optional string argument.
+    fn assertion_failed(
+        &self,
+        message: Option<&Argument>,
+        details: Option<String>,
+    ) -> Result<RuntimeError, RuntimeError> {
+        let mut text = String::from("Assertion failed");
+
+        if let Some(message) = message {
+            text.push_str(": ");
+            text.push_str(&self.read_string(message)?);
+        }
+
+        if let Some(details) = details {
+            text.push_str(&format!(" ({details})"));
+        }
+
+        Ok(RuntimeError::new(text, RuntimeErrorKind::AssertionFailed))
+    }
+
     fn register_to_float(&self, register: RegisterKind) -> Result<f32, RuntimeError> {
         match register {
             RegisterKind::Regular { id, mode } => match mode {
@@ -738,6 +1440,7 @@ impl VM {
             },
             RegisterKind::ProgramCounter => Ok(self.pc as f32),
             RegisterKind::StackPointer => Ok(self.sp as f32),
+            RegisterKind::Clock => Ok(self.clock_ticks as f32),
         }
     }
 
@@ -746,10 +1449,7 @@ impl VM {
             Argument::Register { register: kind } => self.register_to_float(*kind),
             Argument::Int { value } => Ok(*value as f32),
             Argument::Float { value } => Ok(*value),
-            _ => Err(RuntimeError::new(
-                format!("Argument {argument} can't be used as value"),
-                RuntimeErrorKind::InvalidType,
-            )),
+            Argument::Pin { id } => Ok(self.host.read_pin(*id)),
         }
     }
 }
@@ -2,6 +2,7 @@
 pub enum VMStatus {
     Idle,
     Yield,
+    Sleeping,
     Running,
     Finished,
     Error,
@@ -1,13 +1,52 @@
-use nano_risc_arch::{Assembly, Limits, SourceUnit};
-use nano_risc_asm::{compiler, parser};
-use nano_risc_vm::{VMStatus, VM};
+use nano_risc_arch::{Assembly, Limits, Location, SourceUnit};
+use nano_risc_asm::{
+    compiler,
+    parser::{self, MemoryResolver, ParsingError, ParsingErrorKind},
+};
+use nano_risc_vm::{Host, VMStatus, VM};
 use serde::{Deserialize, Serialize};
-use std::{panic, rc::Rc};
+use std::{collections::BTreeMap, panic, rc::Rc};
 use wasm_bindgen::prelude::*;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProgramError {}
 
+struct JsHost {
+    dbg_callback: js_sys::Function,
+    pins: BTreeMap<usize, f32>,
+}
+
+impl JsHost {
+    fn print(&self, text: &str) {
+        let value = JsValue::from_str(text);
+        self.dbg_callback
+            .call1(&JsValue::UNDEFINED, &value)
+            .unwrap();
+    }
+}
+
+impl Host for JsHost {
+    fn debug_number(&mut self, value: f32) {
+        self.print(&value.to_string())
+    }
+
+    fn debug_string(&mut self, text: &str) {
+        self.print(text)
+    }
+
+    fn read_pin(&self, id: usize) -> f32 {
+        self.pins.get(&id).copied().unwrap_or_default()
+    }
+
+    fn write_pin(&mut self, id: usize, value: f32) {
+        self.pins.insert(id, value);
+    }
+
+    fn random_seed(&mut self) -> u64 {
+        (js_sys::Math::random() * u64::MAX as f64) as u64
+    }
+}
+
 #[wasm_bindgen(start)]
 pub fn main() {
     panic::set_hook(Box::new(console_error_panic_hook::hook));
@@ -27,30 +66,66 @@ pub fn vm_release(handle: usize) {
 pub fn vm_set_dbg_callback(handle: usize, callback: js_sys::Function) {
     let vm = unsafe { &mut *(handle as *mut VM) };
 
-    vm.set_dbg_callback(Box::new(move |text| {
-        let value = JsValue::from_str(&text);
-        callback.call1(&JsValue::UNDEFINED, &value).unwrap();
+    vm.set_host(Box::new(JsHost {
+        dbg_callback: callback,
+        pins: BTreeMap::new(),
     }))
 }
 
+#[wasm_bindgen]
+pub fn vm_set_seed(handle: usize, seed: u64) {
+    let vm = unsafe { &mut *(handle as *mut VM) };
+
+    vm.set_seed(seed)
+}
+
 #[wasm_bindgen]
 pub fn vm_load_assembly(handle: usize, code: String) -> JsValue {
+    vm_load_assembly_with_files(handle, code, JsValue::NULL)
+}
+
+/// Loads a program that can `.include` the files from `files`, an object
+/// mapping paths to their code. Returns every error found in the program or
+/// `null` when it was loaded.
+#[wasm_bindgen]
+pub fn vm_load_assembly_with_files(handle: usize, code: String, files: JsValue) -> JsValue {
     let vm = unsafe { &mut *(handle as *mut VM) };
     let unit = SourceUnit::new_anonymous(code.as_bytes().to_vec());
+    let mut resolver = MemoryResolver::new();
+
+    if !files.is_null() && !files.is_undefined() {
+        let files: BTreeMap<String, String> = match serde_wasm_bindgen::from_value(files) {
+            Ok(files) => files,
+            Err(error) => {
+                let error = ParsingError::new(
+                    format!("Invalid files: {error}"),
+                    Location::default(),
+                    ParsingErrorKind::IncludeNotFound,
+                );
+
+                return serde_wasm_bindgen::to_value(&[error]).unwrap();
+            }
+        };
+
+        for (path, code) in files {
+            resolver.add_file(&path, code.into_bytes());
+        }
+    }
 
-    let tokens = match parser::parse(&unit) {
+    let (units, tokens) = parser::parse_with_includes(&unit, &resolver);
+    let tokens = match tokens {
         Ok(tokens) => tokens,
-        Err(error) => return serde_wasm_bindgen::to_value(&error).unwrap(),
+        Err(errors) => return serde_wasm_bindgen::to_value(&errors).unwrap(),
     };
 
-    let assembly = match compiler::compile(unit, tokens, &Limits::default()) {
+    let assembly = match compiler::compile_units(units, tokens, &Limits::default()) {
         Ok(assembly) => assembly,
-        Err(error) => return serde_wasm_bindgen::to_value(&error).unwrap(),
+        Err(errors) => return serde_wasm_bindgen::to_value(&errors).unwrap(),
     };
 
     match vm.load_assembly(assembly) {
         Ok(_) => {}
-        Err(error) => return serde_wasm_bindgen::to_value(&error).unwrap(),
+        Err(error) => return serde_wasm_bindgen::to_value(&[error]).unwrap(),
     }
 
     JsValue::NULL
@@ -99,6 +174,7 @@ pub fn vm_get_status(handle: usize) -> usize {
         VMStatus::Running => 2,
         VMStatus::Finished => 3,
         VMStatus::Error => 4,
+        VMStatus::Sleeping => 5,
     }
 }
 
@@ -113,6 +189,29 @@ pub fn vm_tick(handle: usize) -> JsValue {
     }
 }
 
+#[wasm_bindgen]
+pub fn vm_advance_clock(handle: usize, elapsed_millis: u64) {
+    let vm = unsafe { &mut *(handle as *mut VM) };
+
+    vm.advance_clock(elapsed_millis)
+}
+
+/// Sets pin `id` of the host, e.g. to let a program blocked in `wait p0`
+/// continue.
+#[wasm_bindgen]
+pub fn vm_set_pin(handle: usize, id: usize, value: f32) {
+    let vm = unsafe { &mut *(handle as *mut VM) };
+
+    vm.host_mut().write_pin(id, value)
+}
+
+#[wasm_bindgen]
+pub fn vm_get_pin(handle: usize, id: usize) -> f32 {
+    let vm = unsafe { &mut *(handle as *mut VM) };
+
+    vm.host().read_pin(id)
+}
+
 #[wasm_bindgen]
 pub fn vm_reset(handle: usize) {
     let vm = unsafe { &mut *(handle as *mut VM) };